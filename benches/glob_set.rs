@@ -0,0 +1,84 @@
+//! Compares `FlexGlobSet` against matching each glob in turn
+//! over a corpus of 100,000 paths.
+//!
+//! Run with `cargo bench --bench glob_set`.
+
+use file_paths::{FlexGlob, FlexGlobSet, FlexPath};
+use std::time::Instant;
+
+const CORPUS_SIZE: usize = 100_000;
+
+/// A small linear congruential generator, so that the corpus is the same on every run.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+fn corpus() -> Vec<FlexPath> {
+    let dirs = ["src", "lib", "docs", "tests", "vendor", "assets", "tools", "config", "build", "packages"];
+    let subdirs = ["core", "util", "net", "ui", "db", "cli", "api", "io", "auth", "cache", "internal", "generated"];
+    let stems = ["main", "mod", "index", "lib", "helpers", "types", "README", "config", "schema", "handler", "client", "server"];
+    let exts = ["rs", "ts", "js", "md", "json", "toml", "yaml", "png", "svg", "py", "go", "lock", "txt"];
+    let mut rng = Lcg(0x5eed);
+    (0..CORPUS_SIZE).map(|_| {
+        let mut path = dirs[rng.next(dirs.len())].to_owned();
+        for _ in 0..rng.next(4) {
+            path.push('/');
+            path.push_str(subdirs[rng.next(subdirs.len())]);
+        }
+        path.push('/');
+        path.push_str(stems[rng.next(stems.len())]);
+        path.push('.');
+        path.push_str(exts[rng.next(exts.len())]);
+        FlexPath::new_common(&path)
+    }).collect()
+}
+
+fn patterns() -> Vec<String> {
+    let mut patterns: Vec<String> = [
+        "**/*.rs", "**/*.{ts,js}", "**/*.md", "docs/**", "src/core/**", "vendor/**",
+        "**/generated/**", "**/README.md", "Cargo.lock", "**/*.{png,svg}", "tests/**/*.py",
+        "config/*.{yaml,toml}", "build/**", "**/schema.json", "packages/*/index.ts",
+        "!**/*.txt", "**/[a-c]*.go", "src/**/mod.rs", "**/*handler*", "tools/?li/**",
+    ].iter().map(|s| s.to_string()).collect();
+    // ownership files list many specific directories and files
+    for i in 0..100 {
+        patterns.push(format!("src/team{i}/**"));
+        patterns.push(format!("**/owned_by_{i}.rs"));
+        patterns.push(format!("packages/pkg{i}/*.ts"));
+    }
+    patterns
+}
+
+fn main() {
+    let corpus = corpus();
+    let globs: Vec<FlexGlob> = patterns().iter().map(|p| FlexGlob::new(p).unwrap()).collect();
+    let mut set = FlexGlobSet::new();
+    for glob in &globs {
+        set.add(glob.clone());
+    }
+    println!("{} paths, {} patterns", corpus.len(), globs.len());
+
+    let start = Instant::now();
+    let mut looped = 0usize;
+    for path in &corpus {
+        looped += globs.iter().filter(|glob| glob.is_match(path)).count();
+    }
+    let looped_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut indexed = 0usize;
+    for path in &corpus {
+        indexed += set.matches(path).len();
+    }
+    let indexed_time = start.elapsed();
+
+    assert_eq!(looped, indexed, "FlexGlobSet disagrees with per-pattern matching");
+    println!("per-pattern: {looped_time:?} ({looped} matches)");
+    println!("FlexGlobSet: {indexed_time:?} ({indexed} matches)");
+    println!("speedup: {:.1}x", looped_time.as_secs_f64() / indexed_time.as_secs_f64());
+}
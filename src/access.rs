@@ -0,0 +1,216 @@
+/*!
+Access policies of allow and deny rules over paths.
+*/
+
+use super::{flexible, FlexGlob, FlexPath, FlexPathVariant};
+
+/// A kind of access to a path.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Access {
+    /// Reading the path.
+    Read,
+    /// Writing, creating or deleting the path.
+    Write,
+}
+
+/// Whether an [`AccessRule`] allows or denies access.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum AccessEffect {
+    /// The rule allows access.
+    Allow,
+    /// The rule denies access.
+    Deny,
+}
+
+/// The paths an [`AccessRule`] applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessTarget {
+    /// The paths matching a glob.
+    Glob(FlexGlob),
+    /// A directory and every path under it, compared as in [`FlexPath::starts_with`].
+    Subtree(FlexPath),
+}
+
+impl AccessTarget {
+    fn contains(&self, path: &FlexPath) -> bool {
+        match self {
+            Self::Glob(glob) => glob.is_match(path),
+            Self::Subtree(root) => path.starts_with(root),
+        }
+    }
+}
+
+/// A rule of an [`AccessPolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessRule {
+    effect: AccessEffect,
+    accesses: Vec<Access>,
+    target: AccessTarget,
+}
+
+impl AccessRule {
+    /// Constructs a rule with an effect on the given kinds of access to a target.
+    pub fn new(effect: AccessEffect, accesses: &[Access], target: AccessTarget) -> Self {
+        Self { effect, accesses: accesses.to_vec(), target }
+    }
+
+    /// Constructs a rule allowing the given kinds of access to a target.
+    pub fn allow(accesses: &[Access], target: AccessTarget) -> Self {
+        Self::new(AccessEffect::Allow, accesses, target)
+    }
+
+    /// Constructs a rule denying the given kinds of access to a target.
+    pub fn deny(accesses: &[Access], target: AccessTarget) -> Self {
+        Self::new(AccessEffect::Deny, accesses, target)
+    }
+
+    /// Whether the rule allows or denies access.
+    pub fn effect(&self) -> AccessEffect {
+        self.effect
+    }
+
+    /// The kinds of access the rule applies to.
+    pub fn accesses(&self) -> &[Access] {
+        &self.accesses
+    }
+
+    /// The paths the rule applies to.
+    pub fn target(&self) -> &AccessTarget {
+        &self.target
+    }
+
+    /// Indicates whether the rule applies to an access to a path.
+    /// Unlike [`AccessPolicy::decide`], this does not canonicalize `Windows` paths.
+    pub fn applies_to(&self, path: &FlexPath, access: Access) -> bool {
+        self.accesses.contains(&access) && self.target.contains(path)
+    }
+}
+
+impl std::fmt::Display for AccessRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let effect = match self.effect {
+            AccessEffect::Allow => "allow",
+            AccessEffect::Deny => "deny",
+        };
+        let accesses: Vec<&str> = self.accesses.iter().map(|access| match access {
+            Access::Read => "read",
+            Access::Write => "write",
+        }).collect();
+        match &self.target {
+            AccessTarget::Glob(glob) => write!(f, "{} {} glob {}", effect, accesses.join(","), glob.pattern()),
+            AccessTarget::Subtree(root) => write!(f, "{} {} subtree {}", effect, accesses.join(","), root),
+        }
+    }
+}
+
+/// An ordered list of [`AccessRule`]s deciding whether a kind of access
+/// to a path is allowed.
+///
+/// The first rule that applies to an access decides it, and an access
+/// no rule applies to is denied. Rules are evaluated on resolved paths,
+/// so a path such as `/sandbox/../etc/passwd` is decided as `/etc/passwd`.
+/// Untrusted input should be resolved against a base directory with
+/// [`FlexPath::resolve`] before it is decided.
+///
+/// Segments of `Windows` paths are canonicalized as Win32 does before they
+/// are matched: trailing dots and spaces and `:` stream suffixes are removed,
+/// so `C:/a/.env.` and `C:/a/.env::$DATA` are decided as `C:/a/.env`.
+/// An access to a path with a segment that would be left empty is denied.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{Access, AccessPolicy, AccessRule, AccessTarget, FlexGlob, FlexPath};
+///
+/// let mut policy = AccessPolicy::new();
+/// policy.add(AccessRule::deny(&[Access::Read, Access::Write], AccessTarget::Glob(FlexGlob::new("**/.env").unwrap())));
+/// policy.add(AccessRule::allow(&[Access::Read, Access::Write], AccessTarget::Subtree(FlexPath::new_common("/plugins/demo"))));
+/// policy.add(AccessRule::allow(&[Access::Read], AccessTarget::Subtree(FlexPath::new_common("/usr/share"))));
+///
+/// let base = FlexPath::new_common("/plugins/demo");
+/// assert!(policy.is_allowed(&base.resolve("data/cache.db"), Access::Write));
+/// assert!(!policy.is_allowed(&base.resolve("../../etc/passwd"), Access::Read));
+/// assert!(!policy.is_allowed(&FlexPath::new_common("/usr/share/doc"), Access::Write));
+///
+/// let decision = policy.decide(&base.resolve(".env"), Access::Read);
+/// assert_eq!(Some(0), decision.rule());
+/// assert_eq!("deny read,write glob **/.env", policy.rules()[0].to_string());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AccessPolicy {
+    rules: Vec<AccessRule>,
+}
+
+impl AccessPolicy {
+    /// Constructs an `AccessPolicy` without rules, which denies every access.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule after the existing rules, returning its index.
+    pub fn add(&mut self, rule: AccessRule) -> usize {
+        self.rules.push(rule);
+        self.rules.len() - 1
+    }
+
+    /// Returns the rules, in order.
+    pub fn rules(&self) -> &[AccessRule] {
+        &self.rules
+    }
+
+    /// Decides whether an access to a path is allowed, returning the deciding rule.
+    pub fn decide(&self, path: &FlexPath, access: Access) -> AccessDecision {
+        let Some(path) = canonicalize(path) else {
+            return AccessDecision { allowed: false, rule: None };
+        };
+        match self.rules.iter().position(|rule| rule.applies_to(&path, access)) {
+            Some(i) => AccessDecision { allowed: self.rules[i].effect == AccessEffect::Allow, rule: Some(i) },
+            None => AccessDecision { allowed: false, rule: None },
+        }
+    }
+
+    /// Indicates whether an access to a path is allowed.
+    pub fn is_allowed(&self, path: &FlexPath, access: Access) -> bool {
+        self.decide(path, access).is_allowed()
+    }
+}
+
+/// Removes the trailing dots and spaces and the `:` stream suffixes that Win32
+/// ignores in the segments of a `Windows` path, returning `None` if a segment
+/// is left empty.
+fn canonicalize(path: &FlexPath) -> Option<FlexPath> {
+    if path.variant() != FlexPathVariant::Windows {
+        return Some(path.clone());
+    }
+    let text = path.to_string();
+    let (root, rest) = flexible::split_root(&text, FlexPathVariant::Windows);
+    let mut segments: Vec<&str> = vec![];
+    for segment in rest.split('/').filter(|s| !s.is_empty()) {
+        let segment = segment.split(':').next().unwrap_or("").trim_end_matches(['.', ' ']);
+        if segment.is_empty() {
+            return None;
+        }
+        segments.push(segment);
+    }
+    Some(FlexPath::new(&(root.to_owned() + &segments.join("/")), FlexPathVariant::Windows))
+}
+
+/// A decision of an [`AccessPolicy`].
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct AccessDecision {
+    allowed: bool,
+    rule: Option<usize>,
+}
+
+impl AccessDecision {
+    /// Indicates whether the access is allowed.
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+
+    /// The index of the deciding rule, or `None` if no rule applies
+    /// and the access is denied by default.
+    pub fn rule(&self) -> Option<usize> {
+        self.rule
+    }
+}
@@ -0,0 +1,188 @@
+/*!
+Entry names of zip and tar archives.
+*/
+
+use super::{FlexPath, FlexPathVariant};
+use lazy_regex::*;
+
+/// The size of the name field of a ustar header.
+const USTAR_NAME_SIZE: usize = 100;
+/// The size of the prefix field of a ustar header.
+const USTAR_PREFIX_SIZE: usize = 155;
+
+/// A normalized entry name of a zip or tar archive, which is relative,
+/// uses forward slashes and stays under the directory an archive is extracted to.
+///
+/// Backslashes are converted into forward slashes and `.` and empty segments
+/// are removed. `..` segments are resolved, but rejected if they go above
+/// the top of the archive, which protects extraction from "zip slip".
+/// A trailing slash marks a directory entry.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{ArchivePath, ArchivePathError, FlexPath, FlexPathVariant};
+///
+/// let entry = ArchivePath::new(r"docs\.\guide/../index.html").unwrap();
+/// assert_eq!("docs/index.html", entry.as_str());
+/// assert_eq!("/tmp/out/docs/index.html", entry.extract_to(&FlexPath::new_common("/tmp/out")).unwrap().to_string());
+///
+/// assert_eq!(Err(ArchivePathError::LeavesRoot("../../etc/passwd".into())), ArchivePath::new("../../etc/passwd"));
+/// assert_eq!(Err(ArchivePathError::Prefix(r"C:\Windows\evil.dll".into())), ArchivePath::new(r"C:\Windows\evil.dll"));
+///
+/// let stream = ArchivePath::new("docs/index.html:evil").unwrap();
+/// assert!(stream.extract_to(&FlexPath::new(r"C:\out", FlexPathVariant::Windows)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArchivePath {
+    text: String,
+    dir: bool,
+}
+
+impl ArchivePath {
+    /// Normalizes an entry name.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the entry name is empty after normalization, starts with a separator,
+    /// a drive or a UNC prefix, before or after normalization, goes above the top
+    /// of the archive, or contains a NUL character.
+    pub fn new(entry_name: &str) -> Result<Self, ArchivePathError> {
+        if entry_name.contains('\0') {
+            return Err(ArchivePathError::InvalidCharacter(entry_name.to_owned()));
+        }
+        if regex_is_match!(r"^([/\\]{2}|[A-Za-z]:)", entry_name) {
+            return Err(ArchivePathError::Prefix(entry_name.to_owned()));
+        }
+        if regex_is_match!(r"^[/\\]", entry_name) {
+            return Err(ArchivePathError::Absolute(entry_name.to_owned()));
+        }
+        let mut segments: Vec<&str> = vec![];
+        for segment in entry_name.split(['/', '\\']) {
+            match segment {
+                "" | "." => {},
+                ".." => {
+                    if segments.pop().is_none() {
+                        return Err(ArchivePathError::LeavesRoot(entry_name.to_owned()));
+                    }
+                },
+                _ => segments.push(segment),
+            }
+        }
+        if segments.is_empty() {
+            return Err(ArchivePathError::Empty(entry_name.to_owned()));
+        }
+        // `.` and `..` segments may have hidden a drive, as in `./C:/x`
+        if regex_is_match!(r"^[A-Za-z]:", segments[0]) {
+            return Err(ArchivePathError::Prefix(entry_name.to_owned()));
+        }
+        Ok(Self { text: segments.join("/"), dir: entry_name.ends_with(['/', '\\']) })
+    }
+
+    /// Returns the normalized entry name, without a trailing slash.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Indicates whether the entry name ends with a slash, marking a directory.
+    pub fn is_dir(&self) -> bool {
+        self.dir
+    }
+
+    /// Returns the normalized entry name as stored in an archive,
+    /// with a trailing slash for a directory.
+    pub fn entry_name(&self) -> String {
+        if self.dir { self.text.clone() + "/" } else { self.text.clone() }
+    }
+
+    /// Returns the path an entry is extracted to under `root`.
+    /// The result has the variant of `root`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `root` has the `Windows` variant and the entry name contains a colon,
+    /// which would name a drive or an alternate data stream.
+    pub fn extract_to(&self, root: &FlexPath) -> Result<FlexPath, ArchivePathError> {
+        if root.variant() == FlexPathVariant::Windows && self.text.contains(':') {
+            return Err(ArchivePathError::Colon(self.entry_name()));
+        }
+        Ok(root.join_within(&self.text).expect("normalized entry names stay under the root"))
+    }
+
+    /// Splits the entry name, as returned by [`.entry_name`], into the prefix
+    /// and name fields of a ustar header, of at most 155 and 100 bytes.
+    /// Names of at most 100 bytes have an empty prefix.
+    ///
+    /// Returns `None` if the entry name has no slash at which it fits both fields.
+    ///
+    /// [`.entry_name`]: ArchivePath::entry_name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::ArchivePath;
+    ///
+    /// let long = "d".repeat(120) + "/file.txt";
+    /// let (prefix, name) = ArchivePath::new(&long).unwrap().ustar_fields().unwrap();
+    /// assert_eq!(("d".repeat(120), "file.txt".to_owned()), (prefix, name));
+    /// assert_eq!(Some(("".to_owned(), "a/b".to_owned())), ArchivePath::new("a/b").unwrap().ustar_fields());
+    /// assert_eq!(None, ArchivePath::new(&"x".repeat(101)).unwrap().ustar_fields());
+    /// ```
+    pub fn ustar_fields(&self) -> Option<(String, String)> {
+        let name = self.entry_name();
+        if name.len() <= USTAR_NAME_SIZE {
+            return Some((String::new(), name));
+        }
+        // the prefix ends at the last slash that leaves it within its field,
+        // ignoring the trailing slash of a directory
+        let mut length = name.len().min(USTAR_PREFIX_SIZE + 1);
+        if length == name.len() && self.dir {
+            length -= 1;
+        }
+        let i = name.as_bytes()[..length].iter().rposition(|&b| b == b'/')?;
+        let suffix_len = name.len() - i - 1;
+        if i == 0 || suffix_len == 0 || suffix_len > USTAR_NAME_SIZE || i > USTAR_PREFIX_SIZE {
+            return None;
+        }
+        Some((name[..i].to_owned(), name[i + 1..].to_owned()))
+    }
+}
+
+impl std::fmt::Display for ArchivePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.entry_name())
+    }
+}
+
+/// An error returned from [`ArchivePath::new`] and [`ArchivePath::extract_to`],
+/// holding the entry name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchivePathError {
+    /// The entry name has no segments after normalization.
+    Empty(String),
+    /// The entry name starts with a separator.
+    Absolute(String),
+    /// The entry name starts with a drive or UNC prefix.
+    Prefix(String),
+    /// The entry name goes above the top of the archive through `..` segments.
+    LeavesRoot(String),
+    /// The entry name contains a NUL character.
+    InvalidCharacter(String),
+    /// The entry name contains a colon and is extracted under a `Windows` root.
+    Colon(String),
+}
+
+impl std::fmt::Display for ArchivePathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty(name) => write!(f, "empty archive entry name {:?}", name),
+            Self::Absolute(name) => write!(f, "archive entry name {:?} is absolute", name),
+            Self::Prefix(name) => write!(f, "archive entry name {:?} has a drive or UNC prefix", name),
+            Self::LeavesRoot(name) => write!(f, "archive entry name {:?} leaves the archive", name),
+            Self::InvalidCharacter(name) => write!(f, "archive entry name {:?} contains a NUL character", name),
+            Self::Colon(name) => write!(f, "archive entry name {:?} contains a colon", name),
+        }
+    }
+}
+
+impl std::error::Error for ArchivePathError {}
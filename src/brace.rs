@@ -0,0 +1,222 @@
+/*!
+Brace and range expansion of path patterns.
+*/
+
+use super::{FlexPath, FlexPathVariant};
+use lazy_regex::*;
+
+/// The default maximum number of results of [`expand_braces`].
+pub const DEFAULT_BRACE_EXPANSION_LIMIT: usize = 10_000;
+
+/// Expands the braces of a pattern into every resulting path,
+/// in order, keeping duplicates.
+///
+/// Syntax:
+///
+/// - `{a,b,c}` expands to each alternative. Alternatives may be empty,
+///   be nested and contain slashes.
+/// - `{1..5}` and `{5..1}` expand to a numeric range, and `{a..e}` to an alphabetic range.
+///   A third component gives a step, as in `{0..10..5}`.
+/// - `{01..10}` pads numbers with zeros to the width of the wider endpoint.
+/// - Braces without a comma that are not a range expand to their content, so `{a}` is `a`.
+/// - For the `Common` variant, a backslash (`\`) escapes the next character.
+///   For the `Windows` variant, a backslash is a separator.
+///
+/// Results are limited to [`DEFAULT_BRACE_EXPANSION_LIMIT`] paths;
+/// use [`expand_braces_with_limit`] for a different limit.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{expand_braces, FlexPathVariant};
+///
+/// let paths: Vec<String> = expand_braces("out/{debug,release}/lib{a..b}.so", FlexPathVariant::Common)
+///     .unwrap().iter().map(|p| p.to_string()).collect();
+/// assert_eq!(vec!["out/debug/liba.so", "out/debug/libb.so", "out/release/liba.so", "out/release/libb.so"], paths);
+/// ```
+///
+/// # Errors
+///
+/// Fails if the pattern has unbalanced braces or expands to more
+/// than [`DEFAULT_BRACE_EXPANSION_LIMIT`] paths.
+pub fn expand_braces(pattern: &str, variant: FlexPathVariant) -> Result<Vec<FlexPath>, BraceError> {
+    expand_braces_with_limit(pattern, variant, DEFAULT_BRACE_EXPANSION_LIMIT)
+}
+
+/// Expands the braces of a pattern into at most `limit` paths.
+/// See [`expand_braces`] for the syntax.
+///
+/// # Errors
+///
+/// Fails if the pattern has unbalanced braces or expands to more than `limit` paths.
+pub fn expand_braces_with_limit(pattern: &str, variant: FlexPathVariant, limit: usize) -> Result<Vec<FlexPath>, BraceError> {
+    let escapes = match variant {
+        FlexPathVariant::Common => Escapes::Remove,
+        FlexPathVariant::Windows => Escapes::None,
+    };
+    Ok(expand_strings(pattern, escapes, limit)?
+        .iter()
+        .map(|path| FlexPath::new(path, variant))
+        .collect())
+}
+
+/// How [`expand_strings`] treats backslashes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Escapes {
+    /// A backslash escapes the next character and is kept with it.
+    Keep,
+    /// A backslash escapes the next character and is removed.
+    Remove,
+    /// A backslash is an ordinary character.
+    None,
+}
+
+/// Expands the braces of a pattern into strings.
+pub(crate) fn expand_strings(pattern: &str, escapes: Escapes, limit: usize) -> Result<Vec<String>, BraceError> {
+    let mut expansion = Expansion { chars: pattern.chars().collect(), index: 0, escapes, limit, pattern };
+    let r = expansion.sequence(false)?;
+    if expansion.index < expansion.chars.len() {
+        // a closing brace without an opening brace
+        return Err(BraceError::Unbalanced(pattern.to_owned()));
+    }
+    Ok(r)
+}
+
+struct Expansion<'a> {
+    chars: Vec<char>,
+    index: usize,
+    escapes: Escapes,
+    limit: usize,
+    pattern: &'a str,
+}
+
+impl<'a> Expansion<'a> {
+    /// Expands a sequence up to the end of the pattern or, inside braces,
+    /// up to a top-level comma or closing brace.
+    fn sequence(&mut self, in_braces: bool) -> Result<Vec<String>, BraceError> {
+        let mut r = vec![String::new()];
+        while self.index < self.chars.len() {
+            let c = self.chars[self.index];
+            match c {
+                '\\' if self.escapes != Escapes::None && self.index + 1 < self.chars.len() => {
+                    let next = self.chars[self.index + 1];
+                    for s in &mut r {
+                        if self.escapes == Escapes::Keep {
+                            s.push('\\');
+                        }
+                        s.push(next);
+                    }
+                    self.index += 2;
+                },
+                '{' => {
+                    self.index += 1;
+                    let options = match self.range()? {
+                        Some(options) => options,
+                        None => self.alternatives()?,
+                    };
+                    self.check_limit(r.len().saturating_mul(options.len()))?;
+                    r = r.iter().flat_map(|prefix| options.iter().map(move |option| prefix.clone() + option)).collect();
+                },
+                ',' if in_braces => break,
+                '}' => break,
+                _ => {
+                    for s in &mut r {
+                        s.push(c);
+                    }
+                    self.index += 1;
+                },
+            }
+        }
+        Ok(r)
+    }
+
+    /// Expands comma-separated alternatives after an opening brace,
+    /// up to and including the closing brace.
+    fn alternatives(&mut self) -> Result<Vec<String>, BraceError> {
+        let mut options = vec![];
+        loop {
+            options.extend(self.sequence(true)?);
+            self.check_limit(options.len())?;
+            match self.chars.get(self.index) {
+                Some(',') => self.index += 1,
+                Some('}') => {
+                    self.index += 1;
+                    return Ok(options);
+                },
+                _ => return Err(BraceError::Unbalanced(self.pattern.to_owned())),
+            }
+        }
+    }
+
+    /// Expands a range after an opening brace, up to and including
+    /// the closing brace, if the braces hold a range.
+    fn range(&mut self) -> Result<Option<Vec<String>>, BraceError> {
+        let Some(length) = self.chars[self.index..].iter().position(|&c| c == '}') else {
+            return Ok(None);
+        };
+        let body: String = self.chars[self.index..self.index + length].iter().collect();
+        let options = if let Some((_, from, to, step)) = regex_captures!(r"^(-?\d+)\.\.(-?\d+)(?:\.\.(-?\d+))?$", &body) {
+            let (Ok(from_n), Ok(to_n)) = (from.parse::<i64>(), to.parse::<i64>()) else {
+                return Ok(None);
+            };
+            let padded = |s: &str| s.trim_start_matches('-').len() > 1 && s.trim_start_matches('-').starts_with('0');
+            let width = if padded(from) || padded(to) { from.len().max(to.len()) } else { 0 };
+            self.stepped(from_n, to_n, step)?
+                .into_iter()
+                .map(|n| if n < 0 { format!("-{:0>w$}", -n, w = width.saturating_sub(1)) } else { format!("{:0>w$}", n, w = width) })
+                .collect()
+        } else if let Some((_, from, to, step)) = regex_captures!(r"^([A-Za-z])\.\.([A-Za-z])(?:\.\.(-?\d+))?$", &body) {
+            let (from, to) = (from.as_bytes()[0] as i64, to.as_bytes()[0] as i64);
+            self.stepped(from, to, step)?
+                .into_iter()
+                .filter_map(|n| char::from_u32(n as u32).filter(char::is_ascii_alphabetic))
+                .map(String::from)
+                .collect()
+        } else {
+            return Ok(None);
+        };
+        self.index += length + 1;
+        Ok(Some(options))
+    }
+
+    /// Returns the values from `from` to `to`, inclusive. The step's sign is ignored
+    /// and a zero step is one, as the endpoints give the direction.
+    fn stepped(&self, from: i64, to: i64, step: &str) -> Result<Vec<i64>, BraceError> {
+        let step = if step.is_empty() { 1 } else { step.trim_start_matches('-').parse::<u64>().unwrap_or(u64::MAX).max(1) };
+        let count = from.abs_diff(to) / step + 1;
+        self.check_limit(usize::try_from(count).unwrap_or(usize::MAX))?;
+        let step = step as i128;
+        let step = if from <= to { step } else { -step };
+        Ok((0..count as i128).map(|i| (from as i128 + i * step) as i64).collect())
+    }
+
+    fn check_limit(&self, count: usize) -> Result<(), BraceError> {
+        if count > self.limit {
+            return Err(BraceError::TooManyResults { pattern: self.pattern.to_owned(), limit: self.limit });
+        }
+        Ok(())
+    }
+}
+
+/// An error returned from [`expand_braces`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BraceError {
+    /// The pattern has an unclosed brace or a closing brace without an opening brace.
+    Unbalanced(String),
+    /// The pattern expands to more results than the limit.
+    TooManyResults {
+        pattern: String,
+        limit: usize,
+    },
+}
+
+impl std::fmt::Display for BraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unbalanced(pattern) => write!(f, "unbalanced braces in {:?}", pattern),
+            Self::TooManyResults { pattern, limit } => write!(f, "{:?} expands to more than {} results", pattern, limit),
+        }
+    }
+}
+
+impl std::error::Error for BraceError {}
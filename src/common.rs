@@ -8,49 +8,28 @@ use lazy_regex::*;
 
 static PATH_SEPARATOR: Lazy<Regex> = lazy_regex!(r"[/\\]");
 
-pub fn relative(from_path: &str, to_path: &str) -> String {
+/// Finds the relative path from `from_path` to `to_path`, comparing segments with `segment_eq`.
+pub fn relative(from_path: &str, to_path: &str, segment_eq: impl Fn(&str, &str) -> bool) -> String {
     assert!(
-        [from_path.to_owned(), to_path.to_owned()].iter().all(|path| STARTS_WITH_PATH_SEPARATOR.is_match(path)),
-        "file_paths::relative() requires absolute paths as arguments"
+        STARTS_WITH_PATH_SEPARATOR.is_match(from_path) == STARTS_WITH_PATH_SEPARATOR.is_match(to_path),
+        "file_paths::relative() requires either two absolute or two relative paths as arguments"
     );
 
-    let mut r = Vec::<String>::new();
-
-    let mut from_parts: Vec<String> = PATH_SEPARATOR.split(resolve_one(from_path).as_ref()).map(|s| s.to_owned()).collect();
-    let mut to_parts: Vec<String> = PATH_SEPARATOR.split(resolve_one(to_path).as_ref()).map(|s| s.to_owned()).collect();
-
-    // given each path is absolute, each one can contain an empty
-    // initial second part. in that case, remove the empty string,
-    // since the path `"/"` is previously split into a `vec!["", ""]`.
-    let from_parts = remove_empty(&mut from_parts);
-    let to_parts = remove_empty(&mut to_parts);
-
-    fn remove_empty(parts: &mut Vec<String>) -> &mut Vec<String> {
-        if parts[1].is_empty() {
-            parts.remove(1);
-        }
-        parts
-    }
+    // relative paths are compared as if both were under the same base,
+    // keeping the leading `..` segments that climb above it.
+    let from_parts = resolve_segments(from_path);
+    let to_parts = resolve_segments(to_path);
 
-    let mut common_indices = Vec::<usize>::new();
-
-    for i in 0..usize::min(from_parts.len(), to_parts.len()) {
-        if from_parts[i] != to_parts[i] {
-            break;
-        }
-        common_indices.push(i);
-    }
-    for i in common_indices.iter().rev() {
-        let j = common_indices[*i];
-        from_parts.remove(j);
-        to_parts.remove(j);
-    }
-    r.append(&mut Vec::from_iter((0..from_parts.len()).map(|_| "..".to_owned())));
-    r.append(&mut to_parts.clone());
+    let common_count = from_parts.iter().zip(to_parts.iter()).take_while(|(a, b)| segment_eq(a, b)).count();
+    assert!(
+        !from_parts[common_count..].iter().any(|s| s == ".."),
+        "file_paths::relative() requires a starting path that does not climb above the shared base"
+    );
 
-    let r = r.join("/");
-    let r = r.trim_start().to_owned();
-    if r.ends_with('/') { r[..r.len() - 1].to_owned() } else { r }
+    let mut r = Vec::<String>::new();
+    r.append(&mut Vec::from_iter((common_count..from_parts.len()).map(|_| "..".to_owned())));
+    r.extend_from_slice(&to_parts[common_count..]);
+    r.join("/")
 }
 
 #[allow(unused)]
@@ -92,6 +71,25 @@ pub fn resolve_one(path: &str) -> String {
     if starts_with_slash { "/".to_owned() + &r } else { r }
 }
 
+/// Resolves a path into its segments. Leading `..` segments are kept for a relative path
+/// and dropped for an absolute path.
+fn resolve_segments(path: &str) -> Vec<String> {
+    let absolute = STARTS_WITH_PATH_SEPARATOR.is_match(path);
+    let mut r = Vec::<String>::new();
+    for p in PATH_SEPARATOR.split(path) {
+        if p == ".." {
+            if r.last().is_some_and(|s| s != "..") {
+                r.pop();
+            } else if !absolute {
+                r.push(p.to_owned());
+            }
+        } else if !p.is_empty() && p != "." {
+            r.push(p.to_owned());
+        }
+    }
+    r
+}
+
 fn resolve_one_without_starting_sep(path: &str) -> String {
     let mut r = Vec::<String>::new();
     for p in PATH_SEPARATOR.split(path) {
@@ -0,0 +1,785 @@
+/*!
+Normalization and relative paths reproducing the exact output of
+the Node.js, Python and Go standard libraries.
+*/
+
+use super::{flexible, FlexPath, FlexPathVariant, RelativeOutcome};
+use lazy_regex::*;
+
+/// An ecosystem whose path normalization is reproduced byte-for-byte
+/// by [`normalize_with`] and [`relative_with`].
+///
+/// The `Common` variant follows the POSIX flavor of each ecosystem and
+/// the `Windows` variant follows its Windows flavor:
+///
+/// | Conformance | Normalization | Relative path |
+/// |-------------|---------------|---------------|
+/// | `Node` | `path.posix.normalize`, `path.win32.normalize` | `path.posix.relative`, `path.win32.relative` |
+/// | `Python` | `posixpath.normpath`, `ntpath.normpath` | `posixpath.relpath`, `ntpath.relpath` |
+/// | `Go` | `path.Clean`, `filepath.Clean` on Windows | `filepath.Rel` |
+///
+/// These differ in details, such as whether a trailing separator is kept,
+/// whether an empty path becomes `.` and whether `//` is kept at the start of a path.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{normalize_with, Conformance, FlexPathVariant};
+/// let common = FlexPathVariant::Common;
+/// assert_eq!("a/", normalize_with("a/b/../", common, Conformance::Node));
+/// assert_eq!("a", normalize_with("a/b/../", common, Conformance::Python));
+/// assert_eq!("//a", normalize_with("//a", common, Conformance::Python));
+/// assert_eq!("/a", normalize_with("//a", common, Conformance::Go));
+/// assert_eq!(".", normalize_with("", common, Conformance::Go));
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum Conformance {
+    /// Matches Node.js.
+    Node,
+    /// Matches Python 3.11.
+    Python,
+    /// Matches Go. Unlike the other profiles, it is not yet checked
+    /// against vectors recorded from Go itself.
+    Go,
+}
+
+/// Normalizes `path` exactly as the given ecosystem does, with the separators
+/// of the ecosystem, which are backslashes (`\`) for the `Windows` variant.
+///
+/// Unlike [`FlexPath::new`], the result keeps whatever the ecosystem keeps,
+/// such as leading `..` segments, a `.` for an empty path or a trailing separator,
+/// so it is returned as text rather than as a `FlexPath`. This is why there is
+/// no `FlexPath::new_with` taking a `Conformance`.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{normalize_with, Conformance, FlexPathVariant};
+/// let windows = FlexPathVariant::Windows;
+/// assert_eq!(r"C:.", normalize_with("C:", windows, Conformance::Node));
+/// assert_eq!(r"C:", normalize_with("C:", windows, Conformance::Python));
+/// assert_eq!(r"\\srv\share\", normalize_with("//srv/share", windows, Conformance::Node));
+/// ```
+pub fn normalize_with(path: &str, variant: FlexPathVariant, conformance: Conformance) -> String {
+    normalize(path, variant, conformance)
+}
+
+/// Finds the relative path from `from_path` to `to_path` exactly as the given
+/// ecosystem does. The relative path uses the separators of the ecosystem,
+/// which are backslashes (`\`) for the `Windows` variant.
+///
+/// If both paths are equal, Node.js returns an empty string, while
+/// Python and Go return `.`. If both paths are relative, they are compared
+/// as if both were under the same base directory.
+///
+/// This function does not panic. `RelativeOutcome::Unrelated`, holding `to_path`
+/// as a `FlexPath`, is returned where the ecosystem would fail or return
+/// an absolute path: for different drives, for an absolute and a relative path,
+/// or where the result would depend on the current working directory,
+/// such as from `../a` to `b`.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{relative_with, Conformance, FlexPathVariant, RelativeOutcome};
+/// let windows = FlexPathVariant::Windows;
+/// assert_eq!(RelativeOutcome::Relative(r"..\..\two\a".into()), relative_with(r"\\srv\one\a", r"\\srv\two\a", windows, Conformance::Node));
+/// assert!(matches!(relative_with(r"\\srv\one\a", r"\\srv\two\a", windows, Conformance::Python), RelativeOutcome::Unrelated(_)));
+/// assert_eq!(RelativeOutcome::Relative(".".into()), relative_with("a/b", "a/b/", FlexPathVariant::Common, Conformance::Go));
+/// ```
+pub fn relative_with(from_path: &str, to_path: &str, variant: FlexPathVariant, conformance: Conformance) -> RelativeOutcome {
+    let windows = variant == FlexPathVariant::Windows;
+    let relative = match conformance {
+        Conformance::Node | Conformance::Python => {
+            if conformance == Conformance::Python && to_path.is_empty() {
+                None
+            } else {
+                let from_normalized = normalize(from_path, variant, conformance);
+                let to_normalized = normalize(to_path, variant, conformance);
+                let from_parts = split_normalized(&from_normalized, variant, conformance);
+                let to_parts = split_normalized(&to_normalized, variant, conformance);
+                relative_by_segments(&from_parts, &to_parts, variant).map(|segments| {
+                    match (conformance, variant) {
+                        (Conformance::Node, _) if segments.is_empty() => "".to_owned(),
+                        (_, _) if segments.is_empty() => ".".to_owned(),
+                        (Conformance::Python, FlexPathVariant::Windows) => python_nt_join(&segments),
+                        (_, FlexPathVariant::Windows) => segments.join("\\"),
+                        (_, FlexPathVariant::Common) => segments.join("/"),
+                    }
+                })
+            }
+        },
+        Conformance::Go => go_rel(from_path, to_path, windows),
+    };
+    match relative {
+        Some(path) => RelativeOutcome::Relative(path),
+        None => RelativeOutcome::Unrelated(FlexPath::new(to_path, variant)),
+    }
+}
+
+impl FlexPath {
+    /// Finds the relative path from this path to `to_path` exactly as the given
+    /// ecosystem does, as [`relative_with`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{Conformance, FlexPath, RelativeOutcome};
+    /// let from = FlexPath::new_common("/a/b");
+    /// assert_eq!(RelativeOutcome::Relative("".into()), from.relative_with("/a/b/", Conformance::Node));
+    /// assert_eq!(RelativeOutcome::Relative("../c".into()), from.relative_with("/a/c", Conformance::Python));
+    /// ```
+    pub fn relative_with(&self, to_path: &str, conformance: Conformance) -> RelativeOutcome {
+        relative_with(&self.to_string_with_flex_separator(), to_path, self.1, conformance)
+    }
+}
+
+fn normalize(path: &str, variant: FlexPathVariant, conformance: Conformance) -> String {
+    match (conformance, variant) {
+        (Conformance::Node, FlexPathVariant::Common) => node_posix_normalize(path),
+        (Conformance::Node, FlexPathVariant::Windows) => node_win32_normalize(path),
+        (Conformance::Python, FlexPathVariant::Common) => python_posix_normpath(path),
+        (Conformance::Python, FlexPathVariant::Windows) => python_nt_normpath(path),
+        (Conformance::Go, FlexPathVariant::Common) => go_clean(path, false),
+        (Conformance::Go, FlexPathVariant::Windows) => go_clean(path, true),
+    }
+}
+
+/// A normalized path split into a volume, such as a drive, a root flag
+/// and the remaining segments.
+struct NormalizedParts<'a> {
+    volume: &'a str,
+    rooted: bool,
+    segments: Vec<&'a str>,
+}
+
+/// Splits a path normalized by Node.js or Python. Node.js compares an UNC
+/// share as an ordinary segment, so only the server is part of the volume.
+fn split_normalized(path: &str, variant: FlexPathVariant, conformance: Conformance) -> NormalizedParts<'_> {
+    let (volume, rest, separator) = match variant {
+        FlexPathVariant::Common => ("", path, '/'),
+        FlexPathVariant::Windows => {
+            let volume_len = match conformance {
+                Conformance::Python => python_nt_splitdrive(path).0.len(),
+                _ => {
+                    let bytes = path.as_bytes();
+                    if path.starts_with(r"\\") && bytes.get(2).is_some_and(|&c| c != b'\\') {
+                        path[2..].find('\\').map_or(path.len(), |i| i + 2)
+                    } else if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+                        2
+                    } else {
+                        0
+                    }
+                },
+            };
+            (&path[..volume_len], &path[volume_len..], '\\')
+        },
+    };
+    NormalizedParts {
+        volume,
+        rooted: volume.starts_with(r"\\") || rest.starts_with(separator),
+        segments: rest.split(separator).filter(|s| !s.is_empty() && *s != ".").collect(),
+    }
+}
+
+/// Finds the segments of a relative path from `from` to `to`, which are
+/// empty if both paths are equal.
+fn relative_by_segments<'a>(from: &NormalizedParts<'a>, to: &NormalizedParts<'a>, variant: FlexPathVariant) -> Option<Vec<&'a str>> {
+    if !flexible::segment_eq(from.volume, to.volume, variant) || from.rooted != to.rooted {
+        return None;
+    }
+    let common_count = from.segments.iter().zip(to.segments.iter())
+        .take_while(|(a, b)| flexible::segment_eq(a, b, variant))
+        .count();
+    // climbing above the implicit base depends on the working directory.
+    if from.segments[common_count..].contains(&"..") {
+        return None;
+    }
+    let mut r: Vec<&str> = vec![".."; from.segments.len() - common_count];
+    r.extend_from_slice(&to.segments[common_count..]);
+    Some(r)
+}
+
+// Node.js
+
+/// Port of Node.js `normalizeString`, which resolves `.` and `..` segments.
+fn node_normalize_string(path: &str, allow_above_root: bool, separator: char, is_separator: fn(u8) -> bool) -> String {
+    let bytes = path.as_bytes();
+    let mut res = String::new();
+    let mut last_segment_length = 0;
+    let mut last_slash: Option<usize> = None;
+    let mut dots = 0;
+    let mut code = 0;
+    for i in 0..=bytes.len() {
+        if i < bytes.len() {
+            code = bytes[i];
+        } else if is_separator(code) {
+            break;
+        } else {
+            code = b'/';
+        }
+        if is_separator(code) {
+            let segment_start = last_slash.map_or(0, |s| s + 1);
+            if segment_start == i || dots == 1 {
+                // an empty segment or `.`
+            } else if dots == 2 {
+                let is_parent_segment = res.len() >= 2 && last_segment_length == 2 && res.ends_with("..");
+                if !is_parent_segment && !res.is_empty() {
+                    match res.rfind(separator) {
+                        Some(index) => {
+                            res.truncate(index);
+                            last_segment_length = res.len() - res.rfind(separator).map_or(0, |i| i + 1);
+                        },
+                        None => {
+                            res.clear();
+                            last_segment_length = 0;
+                        },
+                    }
+                } else if allow_above_root {
+                    if !res.is_empty() {
+                        res.push(separator);
+                    }
+                    res.push_str("..");
+                    last_segment_length = 2;
+                }
+            } else {
+                if !res.is_empty() {
+                    res.push(separator);
+                }
+                res.push_str(&path[segment_start..i]);
+                last_segment_length = i - segment_start;
+            }
+            last_slash = Some(i);
+            dots = 0;
+        } else if code == b'.' && dots != -1 {
+            dots += 1;
+        } else {
+            dots = -1;
+        }
+    }
+    res
+}
+
+fn node_posix_normalize(path: &str) -> String {
+    if path.is_empty() {
+        return ".".into();
+    }
+    let is_absolute = path.starts_with('/');
+    let trailing_separator = path.ends_with('/');
+    let mut path = node_normalize_string(path, !is_absolute, '/', |c| c == b'/');
+    if path.is_empty() {
+        if is_absolute {
+            return "/".into();
+        }
+        return if trailing_separator { "./".into() } else { ".".into() };
+    }
+    if trailing_separator {
+        path.push('/');
+    }
+    if is_absolute { "/".to_owned() + &path } else { path }
+}
+
+fn is_win32_separator(code: u8) -> bool {
+    code == b'/' || code == b'\\'
+}
+
+/// Port of the Node.js `isWindowsReservedName` function. As in Node.js,
+/// a missing colon excludes the last character of the path.
+fn node_is_windows_reserved_name(path: &str, colon_index: Option<usize>) -> bool {
+    let device_part = match colon_index {
+        Some(i) => &path[..i],
+        None => path.char_indices().last().map_or("", |(i, _)| &path[..i]),
+    };
+    let device_part = device_part.to_uppercase();
+    matches!(device_part.as_str(), "CON" | "PRN" | "AUX" | "NUL" | "COM\u{b9}" | "COM\u{b2}" | "COM\u{b3}" | "LPT\u{b9}" | "LPT\u{b2}" | "LPT\u{b3}")
+        || regex_is_match!(r"^(COM|LPT)[1-9]$", &device_part)
+}
+
+fn node_win32_normalize(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let len = bytes.len();
+    if len == 0 {
+        return ".".into();
+    }
+    let mut root_end = 0;
+    let mut device: Option<String> = None;
+    let mut is_absolute = false;
+    let code = bytes[0];
+
+    if len == 1 {
+        return if code == b'/' { "\\".into() } else { path.into() };
+    }
+    if is_win32_separator(code) {
+        // possible UNC root
+        is_absolute = true;
+        if is_win32_separator(bytes[1]) {
+            let mut j = 2;
+            let mut last = j;
+            while j < len && !is_win32_separator(bytes[j]) {
+                j += 1;
+            }
+            if j < len && j != last {
+                let first_part = &path[last..j];
+                last = j;
+                while j < len && is_win32_separator(bytes[j]) {
+                    j += 1;
+                }
+                if j < len && j != last {
+                    last = j;
+                    while j < len && !is_win32_separator(bytes[j]) {
+                        j += 1;
+                    }
+                    if j == len || j != last {
+                        if first_part == "." || first_part == "?" {
+                            // a device root, such as `\\.\PHYSICALDRIVE0`
+                            device = Some(format!(r"\\{}", first_part));
+                            root_end = 4;
+                        } else if j == len {
+                            // an UNC root only
+                            return format!(r"\\{}\{}\", first_part, &path[last..]);
+                        } else {
+                            // an UNC root with leftovers
+                            device = Some(format!(r"\\{}\{}", first_part, &path[last..j]));
+                            root_end = j;
+                        }
+                    }
+                }
+            }
+        } else {
+            root_end = 1;
+        }
+    } else if let Some(colon_index) = path.find(':').filter(|&i| i > 0) {
+        if code.is_ascii_alphabetic() && colon_index == 1 {
+            device = Some(path[..2].to_owned());
+            root_end = 2;
+            if len > 2 && is_win32_separator(bytes[2]) {
+                is_absolute = true;
+                root_end = 3;
+            }
+        } else if node_is_windows_reserved_name(path, Some(colon_index)) {
+            device = Some(path[..colon_index + 1].to_owned());
+            root_end = colon_index + 1;
+        }
+    }
+
+    let mut tail = if root_end < len {
+        node_normalize_string(&path[root_end..], !is_absolute, '\\', is_win32_separator)
+    } else {
+        String::new()
+    };
+    if tail.is_empty() && !is_absolute {
+        tail = ".".into();
+    }
+    if !tail.is_empty() && is_win32_separator(bytes[len - 1]) {
+        tail.push('\\');
+    }
+    if !is_absolute && device.is_none() && path.contains(':') {
+        // ensure that the tail of a relative path is not
+        // interpreted as a drive (CVE-2024-36139).
+        let tail_bytes = tail.as_bytes();
+        if tail_bytes.len() >= 2 && tail_bytes[0].is_ascii_alphabetic() && tail_bytes[1] == b':' {
+            return format!(r".\{}", tail);
+        }
+        for (index, _) in path.match_indices(':') {
+            if index == len - 1 || is_win32_separator(bytes[index + 1]) {
+                return format!(r".\{}", tail);
+            }
+        }
+    }
+    if node_is_windows_reserved_name(path, path.find(':')) {
+        return format!(r".\{}{}", device.unwrap_or_default(), tail);
+    }
+    match device {
+        None => if is_absolute { format!(r"\{}", tail) } else { tail },
+        Some(device) => if is_absolute { format!(r"{}\{}", device, tail) } else { device + &tail },
+    }
+}
+
+// Python
+
+fn python_posix_normpath(path: &str) -> String {
+    if path.is_empty() {
+        return ".".into();
+    }
+    // exactly two leading slashes are kept, as POSIX allows
+    // them to have an implementation-defined meaning.
+    let initial_slashes = if path.starts_with("//") && !path.starts_with("///") {
+        2
+    } else if path.starts_with('/') {
+        1
+    } else {
+        0
+    };
+    let mut new_comps: Vec<&str> = vec![];
+    for comp in path.split('/') {
+        if comp.is_empty() || comp == "." {
+            continue;
+        }
+        if comp != ".." || (initial_slashes == 0 && new_comps.is_empty()) || new_comps.last() == Some(&"..") {
+            new_comps.push(comp);
+        } else {
+            new_comps.pop();
+        }
+    }
+    let r = "/".repeat(initial_slashes) + &new_comps.join("/");
+    if r.is_empty() { ".".into() } else { r }
+}
+
+/// Port of Python 3.11 `ntpath.splitdrive`, returning a drive or UNC share
+/// and the remaining path.
+fn python_nt_splitdrive(path: &str) -> (&str, &str) {
+    let normp = path.replace('/', "\\");
+    if normp.len() >= 2 {
+        if normp.starts_with(r"\\") {
+            let start = if normp.get(..8).is_some_and(|p| p.eq_ignore_ascii_case(r"\\?\UNC\")) { 8 } else { 2 };
+            let Some(index) = normp[start..].find('\\').map(|i| i + start) else {
+                return (path, "");
+            };
+            let Some(index2) = normp[index + 1..].find('\\').map(|i| i + index + 1) else {
+                return (path, "");
+            };
+            return path.split_at(index2);
+        }
+        if normp.as_bytes()[1] == b':' {
+            return path.split_at(2);
+        }
+    }
+    ("", path)
+}
+
+/// Port of Python 3.11 `ntpath.join` for segments without separators,
+/// where a segment such as `c:` is taken as a drive.
+fn python_nt_join(segments: &[&str]) -> String {
+    let (mut result_drive, result_path) = python_nt_splitdrive(segments[0]);
+    let mut result_path = result_path.to_owned();
+    for segment in &segments[1..] {
+        let (p_drive, p_path) = python_nt_splitdrive(segment);
+        if !p_drive.is_empty() && p_drive != result_drive {
+            if p_drive.to_lowercase() != result_drive.to_lowercase() {
+                // a different drive ignores the previous segments
+                result_drive = p_drive;
+                result_path = p_path.to_owned();
+                continue;
+            }
+            result_drive = p_drive;
+        }
+        if !result_path.is_empty() && !result_path.ends_with('\\') {
+            result_path.push('\\');
+        }
+        result_path.push_str(p_path);
+    }
+    if !result_path.is_empty() && !result_drive.is_empty() && !result_drive.ends_with(':') {
+        return result_drive.to_owned() + "\\" + &result_path;
+    }
+    result_drive.to_owned() + &result_path
+}
+
+fn python_nt_normpath(path: &str) -> String {
+    let path = path.replace('/', "\\");
+    let (prefix, path) = python_nt_splitdrive(&path);
+    let mut prefix = prefix.to_owned();
+    let mut path = path;
+    // collapse initial backslashes
+    if path.starts_with('\\') {
+        prefix.push('\\');
+        path = path.trim_start_matches('\\');
+    }
+    let mut comps: Vec<&str> = path.split('\\').collect();
+    let mut i = 0;
+    while i < comps.len() {
+        if comps[i].is_empty() || comps[i] == "." {
+            comps.remove(i);
+        } else if comps[i] == ".." {
+            if i > 0 && comps[i - 1] != ".." {
+                comps.drain(i - 1..=i);
+                i -= 1;
+            } else if i == 0 && prefix.ends_with('\\') {
+                comps.remove(i);
+            } else {
+                i += 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    if prefix.is_empty() && comps.is_empty() {
+        comps.push(".");
+    }
+    prefix + &comps.join("\\")
+}
+
+// Go
+
+fn go_is_separator(c: u8, windows: bool) -> bool {
+    c == b'/' || (windows && c == b'\\')
+}
+
+fn go_from_slash(path: &str, windows: bool) -> String {
+    if windows { path.replace('/', "\\") } else { path.to_owned() }
+}
+
+/// Port of Go's `pathHasPrefixFold`, which also requires that
+/// the prefix is followed by a separator or the end of the path.
+fn go_path_has_prefix_fold(s: &[u8], prefix: &[u8]) -> bool {
+    if s.len() < prefix.len() {
+        return false;
+    }
+    for (&a, &b) in s.iter().zip(prefix.iter()) {
+        if go_is_separator(b, true) {
+            if !go_is_separator(a, true) {
+                return false;
+            }
+        } else if !a.eq_ignore_ascii_case(&b) {
+            return false;
+        }
+    }
+    s.len() == prefix.len() || go_is_separator(s[prefix.len()], true)
+}
+
+/// Port of Go's `uncLen`, which finds the end of the UNC share.
+fn go_unc_len(path: &[u8], prefix_len: usize) -> usize {
+    let mut count = 0;
+    for (i, &c) in path.iter().enumerate().skip(prefix_len) {
+        if go_is_separator(c, true) {
+            count += 1;
+            if count == 2 {
+                return i;
+            }
+        }
+    }
+    path.len()
+}
+
+/// Port of Go's `volumeNameLen` for Windows.
+fn go_volume_name_len(path: &str, windows: bool) -> usize {
+    let p = path.as_bytes();
+    if !windows {
+        return 0;
+    }
+    if p.len() >= 2 && p[1] == b':' {
+        // a drive letter, which is not required to be in `A-Z`.
+        return 2;
+    }
+    if p.is_empty() || !go_is_separator(p[0], true) {
+        return 0;
+    }
+    if go_path_has_prefix_fold(p, br"\\.\UNC") {
+        return go_unc_len(p, br"\\.\UNC\".len());
+    }
+    if go_path_has_prefix_fold(p, br"\\.") || go_path_has_prefix_fold(p, br"\\?") || go_path_has_prefix_fold(p, br"\??") {
+        // a local device path, whose next segment is part of the volume.
+        if p.len() == 3 {
+            return 3;
+        }
+        return match p[4..].iter().position(|&c| go_is_separator(c, true)) {
+            Some(i) => 4 + i,
+            None => p.len(),
+        };
+    }
+    if p.len() >= 2 && go_is_separator(p[1], true) {
+        return go_unc_len(p, 2);
+    }
+    0
+}
+
+/// Port of Go's `lazybuf`, which avoids allocating while the output
+/// is a prefix of the input.
+struct GoLazyBuf<'a> {
+    path: &'a [u8],
+    buf: Option<Vec<u8>>,
+    w: usize,
+    vol_and_path: &'a str,
+    vol_len: usize,
+}
+
+impl GoLazyBuf<'_> {
+    fn index(&self, i: usize) -> u8 {
+        match &self.buf {
+            Some(buf) => buf[i],
+            None => self.path[i],
+        }
+    }
+
+    fn append(&mut self, c: u8) {
+        if self.buf.is_none() {
+            if self.w < self.path.len() && self.path[self.w] == c {
+                self.w += 1;
+                return;
+            }
+            let mut buf = vec![0; self.path.len()];
+            buf[..self.w].copy_from_slice(&self.path[..self.w]);
+            self.buf = Some(buf);
+        }
+        let buf = self.buf.as_mut().unwrap();
+        if self.w == buf.len() {
+            buf.push(c);
+        } else {
+            buf[self.w] = c;
+        }
+        self.w += 1;
+    }
+
+    fn prepend(&mut self, prefix: &[u8]) {
+        let buf = self.buf.as_mut().unwrap();
+        buf.splice(0..0, prefix.iter().copied());
+        self.w += prefix.len();
+    }
+
+    fn string(&self) -> String {
+        match &self.buf {
+            None => self.vol_and_path[..self.vol_len + self.w].to_owned(),
+            Some(buf) => self.vol_and_path[..self.vol_len].to_owned() + &String::from_utf8_lossy(&buf[..self.w]),
+        }
+    }
+}
+
+/// Port of Go's `path.Clean` and, for Windows, `filepath.Clean`.
+fn go_clean(original_path: &str, windows: bool) -> String {
+    let separator = if windows { b'\\' } else { b'/' };
+    let vol_len = go_volume_name_len(original_path, windows);
+    let path = &original_path.as_bytes()[vol_len..];
+    if path.is_empty() {
+        let o = original_path.as_bytes();
+        if vol_len > 1 && go_is_separator(o[0], windows) && go_is_separator(o[1], windows) {
+            // an UNC volume
+            return go_from_slash(original_path, windows);
+        }
+        return original_path.to_owned() + ".";
+    }
+    let rooted = go_is_separator(path[0], windows);
+    let n = path.len();
+    let mut out = GoLazyBuf { path, buf: None, w: 0, vol_and_path: original_path, vol_len };
+    let (mut r, mut dotdot) = (0, 0);
+    if rooted {
+        out.append(separator);
+        (r, dotdot) = (1, 1);
+    }
+    while r < n {
+        if go_is_separator(path[r], windows) {
+            // an empty segment
+            r += 1;
+        } else if path[r] == b'.' && (r + 1 == n || go_is_separator(path[r + 1], windows)) {
+            // a `.` segment
+            r += 1;
+        } else if path[r] == b'.' && path[r + 1] == b'.' && (r + 2 == n || go_is_separator(path[r + 2], windows)) {
+            // a `..` segment: remove to the last separator
+            r += 2;
+            if out.w > dotdot {
+                out.w -= 1;
+                while out.w > dotdot && !go_is_separator(out.index(out.w), windows) {
+                    out.w -= 1;
+                }
+            } else if !rooted {
+                // a leading `..` is kept for a relative path
+                if out.w > 0 {
+                    out.append(separator);
+                }
+                out.append(b'.');
+                out.append(b'.');
+                dotdot = out.w;
+            }
+        } else {
+            // a real segment: add a separator if needed
+            if (rooted && out.w != 1) || (!rooted && out.w != 0) {
+                out.append(separator);
+            }
+            while r < n && !go_is_separator(path[r], windows) {
+                out.append(path[r]);
+                r += 1;
+            }
+        }
+    }
+    if out.w == 0 {
+        out.append(b'.');
+    }
+    if windows {
+        go_post_clean(&mut out);
+    }
+    go_from_slash(&out.string(), windows)
+}
+
+/// Port of Go's `postClean` for Windows, which keeps a cleaned relative path
+/// from being interpreted as a drive or as a `\??\` path.
+fn go_post_clean(out: &mut GoLazyBuf) {
+    if out.vol_len != 0 {
+        return;
+    }
+    let Some(buf) = &out.buf else {
+        return;
+    };
+    let first_segment_has_colon = buf.iter().take_while(|&&c| !go_is_separator(c, true)).any(|&c| c == b':');
+    if first_segment_has_colon {
+        out.prepend(b".\\");
+    } else if buf.len() >= 3 && go_is_separator(buf[0], true) && buf[1] == b'?' && buf[2] == b'?' {
+        out.prepend(b"\\.");
+    }
+}
+
+fn go_same_word(a: &str, b: &str, windows: bool) -> bool {
+    if windows { a.to_lowercase() == b.to_lowercase() } else { a == b }
+}
+
+/// Port of Go's `filepath.Rel`, returning `None` where it fails.
+fn go_rel(base_path: &str, targ_path: &str, windows: bool) -> Option<String> {
+    let separator = if windows { '\\' } else { '/' };
+    let base_vol = go_from_slash(&base_path[..go_volume_name_len(base_path, windows)], windows);
+    let targ_vol = go_from_slash(&targ_path[..go_volume_name_len(targ_path, windows)], windows);
+    let base = go_clean(base_path, windows);
+    let targ = go_clean(targ_path, windows);
+    if go_same_word(&targ, &base, windows) {
+        return Some(".".into());
+    }
+    let mut base = &base[base_vol.len()..];
+    let targ = &targ[targ_vol.len()..];
+    if base == "." {
+        base = "";
+    } else if base.is_empty() && go_volume_name_len(&base_vol, windows) > 2 {
+        // an UNC volume
+        base = if windows { "\\" } else { "/" };
+    }
+    let base_slashed = base.starts_with(separator);
+    let targ_slashed = targ.starts_with(separator);
+    if base_slashed != targ_slashed || !go_same_word(&base_vol, &targ_vol, windows) {
+        return None;
+    }
+    // position base[b0..bi] and targ[t0..ti] at the first differing segments.
+    let (base_bytes, targ_bytes) = (base.as_bytes(), targ.as_bytes());
+    let (bl, tl) = (base.len(), targ.len());
+    let (mut b0, mut bi, mut t0, mut ti) = (0, 0, 0, 0);
+    loop {
+        while bi < bl && base_bytes[bi] != separator as u8 {
+            bi += 1;
+        }
+        while ti < tl && targ_bytes[ti] != separator as u8 {
+            ti += 1;
+        }
+        if !go_same_word(&targ[t0..ti], &base[b0..bi], windows) || (bi == bl && ti == tl) {
+            break;
+        }
+        if bi < bl {
+            bi += 1;
+        }
+        if ti < tl {
+            ti += 1;
+        }
+        b0 = bi;
+        t0 = ti;
+    }
+    if &base[b0..bi] == ".." {
+        return None;
+    }
+    if b0 != bl {
+        let seps = base[b0..bl].matches(separator).count();
+        let mut r = "..".to_owned();
+        for _ in 0..seps {
+            r.push(separator);
+            r.push_str("..");
+        }
+        if t0 != tl {
+            r.push(separator);
+            r.push_str(&targ[t0..]);
+        }
+        return Some(r);
+    }
+    Some(targ[t0..].to_owned())
+}
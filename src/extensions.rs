@@ -0,0 +1,160 @@
+/*!
+A registry of compound extensions, such as `.tar.gz`, which are
+treated as a single extension by some `FlexPath` methods.
+*/
+
+use super::{extension_arg, matching_extension_start, FlexPathVariant};
+use lazy_regex::Lazy;
+use unicode_normalization::UnicodeNormalization;
+
+/// A registry of compound extensions, which are extensions
+/// consisting of multiple dot-separated parts, such as `.tar.gz`, `.d.ts` and `.min.js`.
+///
+/// Methods such as [`FlexPath::extension_with`](crate::FlexPath::extension_with)
+/// treat a registered compound extension as a single extension.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{CompoundExtensions, FlexPath};
+///
+/// let mut compound = CompoundExtensions::with_defaults();
+/// compound.add("spec.ts");
+/// assert_eq!(Some("d.ts".into()), FlexPath::new_common("lib.d.ts").extension_with(&compound));
+/// assert_eq!("lib.js", FlexPath::new_common("lib.d.ts").change_last_extension_with("js", &compound).to_string());
+/// assert_eq!("a", FlexPath::new_common("a.spec.ts").file_stem_with(&compound));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompoundExtensions {
+    extensions: Vec<String>,
+}
+
+impl CompoundExtensions {
+    /// Constructs a registry without compound extensions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a registry containing built-in compound extensions:
+    /// archive extensions such as `.tar.gz`, TypeScript declaration extensions
+    /// such as `.d.ts`, and minified extensions such as `.min.js`.
+    pub fn with_defaults() -> Self {
+        let mut r = Self::new();
+        for extension in [
+            "tar.gz", "tar.bz2", "tar.xz", "tar.zst", "tar.lz", "tar.lzma", "tar.Z",
+            "d.ts", "d.mts", "d.cts",
+            "min.js", "min.mjs", "min.css",
+            "js.map", "css.map",
+        ] {
+            r.add(extension);
+        }
+        r
+    }
+
+    /// Registers a compound extension.
+    /// This method adds any lacking dot (`.`) prefix automatically to the
+    /// `extension` argument.
+    pub fn add(&mut self, extension: &str) {
+        let extension = extension_arg(extension);
+        if !self.extensions.contains(&extension) {
+            self.extensions.push(extension);
+        }
+    }
+
+    /// Indicates whether a compound extension is registered.
+    /// This method adds any lacking dot (`.`) prefix automatically to the
+    /// `extension` argument.
+    pub fn contains(&self, extension: &str) -> bool {
+        self.extensions.contains(&extension_arg(extension))
+    }
+
+    /// Returns the registered compound extensions, each with a dot prefix.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.extensions.iter().map(|ext| ext.as_str())
+    }
+
+    /// Returns the index where the longest registered compound extension
+    /// of a file name starts, comparing extensions under `matching`.
+    pub(crate) fn find(&self, name: &str, matching: ExtensionMatching) -> Option<usize> {
+        self.iter().filter_map(|ext| matching_extension_start(name, ext, matching)).min()
+    }
+
+    /// Indicates whether a compound extension, with a dot prefix,
+    /// is registered, comparing extensions under `matching`.
+    pub(crate) fn contains_matching(&self, extension: &str, matching: ExtensionMatching) -> bool {
+        self.iter().any(|ext| matching.eq(ext, extension))
+    }
+}
+
+/// The built-in compound extensions, used by
+/// [`FlexPath::change_last_extension`](crate::FlexPath::change_last_extension).
+pub(crate) static DEFAULT_COMPOUND_EXTENSIONS: Lazy<CompoundExtensions> = Lazy::new(CompoundExtensions::with_defaults);
+
+/// Options for comparing extensions in methods such as
+/// [`FlexPath::has_extension_with`](crate::FlexPath::has_extension_with).
+///
+/// Methods without these options, such as [`FlexPath::has_extension`](crate::FlexPath::has_extension),
+/// use [`ExtensionMatching::for_variant`].
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{ExtensionMatching, FlexPath};
+/// let matching = ExtensionMatching::exact().case_insensitive(true);
+/// assert!(FlexPath::new_common("photo.JPG").has_extension_with("jpg", matching));
+/// assert!(!FlexPath::new_common("photo.JPG").has_extension_with("jpg", ExtensionMatching::exact()));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExtensionMatching {
+    case_insensitive: bool,
+    unicode_normalized: bool,
+}
+
+impl ExtensionMatching {
+    /// Options comparing extensions exactly, code point by code point.
+    pub const fn exact() -> Self {
+        Self { case_insensitive: false, unicode_normalized: false }
+    }
+
+    /// The default options for a variant. Extensions are compared after
+    /// Unicode normalization, and are compared case-insensitively
+    /// for the `Windows` variant.
+    pub const fn for_variant(variant: FlexPathVariant) -> Self {
+        Self {
+            case_insensitive: matches!(variant, FlexPathVariant::Windows),
+            unicode_normalized: true,
+        }
+    }
+
+    /// Sets whether extensions are compared case-insensitively.
+    pub const fn case_insensitive(self, value: bool) -> Self {
+        Self { case_insensitive: value, ..self }
+    }
+
+    /// Sets whether extensions are compared after normalizing them
+    /// to the Unicode Normalization Form C (NFC), so that a precomposed `é`
+    /// matches an `e` followed by a combining acute accent.
+    pub const fn unicode_normalized(self, value: bool) -> Self {
+        Self { unicode_normalized: value, ..self }
+    }
+
+    /// Indicates whether extensions are compared case-insensitively.
+    pub const fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Indicates whether extensions are compared after Unicode normalization.
+    pub const fn is_unicode_normalized(&self) -> bool {
+        self.unicode_normalized
+    }
+
+    /// Indicates whether two strings are equal under these options.
+    pub(crate) fn eq(&self, a: &str, b: &str) -> bool {
+        a == b || (self.case_insensitive || self.unicode_normalized) && self.fold(a) == self.fold(b)
+    }
+
+    fn fold(&self, s: &str) -> String {
+        let s = if self.case_insensitive { s.to_lowercase() } else { s.to_owned() };
+        if self.unicode_normalized { s.nfc().collect() } else { s }
+    }
+}
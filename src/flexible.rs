@@ -72,27 +72,92 @@ pub fn is_absolute(path: &str, manipulation: FlexPathVariant) -> bool {
 }
 
 pub fn relative(from_path: &str, to_path: &str, manipulation: FlexPathVariant) -> String {
+    try_relative(from_path, to_path, manipulation).unwrap_or_else(|| resolve_one(to_path, manipulation))
+}
+
+/// Finds the relative path from `from_path` to `to_path`, returning `None`
+/// if both paths have different prefixes, such as different drives
+/// or different UNC shares.
+pub fn try_relative(from_path: &str, to_path: &str, manipulation: FlexPathVariant) -> Option<String> {
     match manipulation {
         FlexPathVariant::Common =>
-            crate::common::relative(from_path, to_path),
+            Some(crate::common::relative(from_path, to_path, |a, b| segment_eq(a, b, manipulation))),
         FlexPathVariant::Windows => {
+            let absolute = [from_path, to_path].map(|path| is_absolute(path, manipulation));
             assert!(
-                [from_path.to_owned(), to_path.to_owned()].iter().all(|path| is_absolute(path, manipulation)),
-                "file_paths::argumented::relative() requires absolute paths as arguments"
+                absolute[0] == absolute[1],
+                "file_paths::relative() requires either two absolute or two relative paths as arguments"
             );
-            let mut paths = [from_path, to_path].map(|s| s.to_owned());
-            let prefixes: Vec<String> = paths.iter().map(|path| STARTS_WITH_WINDOWS_PATH_PREFIX_OR_SLASH.find(path.as_ref()).unwrap().as_str().into()).collect();
-            let prefix = prefixes[0].clone();
-            if prefix != prefixes[1] {
-                return resolve_one(to_path, manipulation);
+            if !absolute[0] {
+                return Some(crate::common::relative(from_path, to_path, |a, b| segment_eq(a, b, manipulation)));
             }
-            for path in &mut paths {
-                *path = path[prefix.len()..].to_owned();
-                if !STARTS_WITH_PATH_SEPARATOR.is_match(path.as_ref()) {
-                    *path = "/".to_owned() + path.as_ref();
-                }
+            let paths = [from_path, to_path].map(|path| resolve_one(path, manipulation));
+            let prefixes = [&paths[0], &paths[1]].map(|path| windows_prefix(path));
+            if !segment_eq(prefixes[0], prefixes[1], manipulation) {
+                return None;
             }
-            crate::common::relative(paths[0].as_ref(), paths[1].as_ref())
+            let paths = [&paths[0], &paths[1]].map(|path| "/".to_owned() + &path[prefixes[0].len()..]);
+            Some(crate::common::relative(paths[0].as_ref(), paths[1].as_ref(), |a, b| segment_eq(a, b, manipulation)))
         },
     }
-}
\ No newline at end of file
+}
+
+/// Returns the prefix of a resolved absolute Windows path: a drive (`C:`),
+/// an UNC server and share (`\\server/share`), or an empty string
+/// for a path that starts with a slash.
+pub fn windows_prefix(path: &str) -> &str {
+    if let Some(rest) = path.strip_prefix(UNC_PREFIX) {
+        let share_end = rest.match_indices('/').nth(1).map_or(rest.len(), |(i, _)| i);
+        return &path[..UNC_PREFIX.len() + share_end];
+    }
+    STARTS_WITH_WINDOWS_PATH_PREFIX.find(path).map_or("", |m| m.as_str())
+}
+
+/// Splits a resolved path into its root (such as `/`, `C:/` or `\\`) and the
+/// remaining segments.
+pub fn split_root(path: &str, manipulation: FlexPathVariant) -> (&str, &str) {
+    let root_len = match manipulation {
+        FlexPathVariant::Common => {
+            if STARTS_WITH_PATH_SEPARATOR.is_match(path) { 1 } else { 0 }
+        },
+        FlexPathVariant::Windows => {
+            if path.starts_with(UNC_PREFIX) {
+                UNC_PREFIX.len()
+            } else if let Some(m) = STARTS_WITH_WINDOWS_PATH_PREFIX.find(path) {
+                m.end() + if path[m.end()..].starts_with('/') { 1 } else { 0 }
+            } else if STARTS_WITH_PATH_SEPARATOR.is_match(path) {
+                1
+            } else {
+                0
+            }
+        },
+    };
+    (&path[..root_len], &path[root_len..])
+}
+
+/// Compares two path segments. Segments of the `Windows` variant
+/// are compared case-insensitively.
+pub fn segment_eq(a: &str, b: &str, manipulation: FlexPathVariant) -> bool {
+    match manipulation {
+        FlexPathVariant::Common => a == b,
+        FlexPathVariant::Windows => a == b || a.to_lowercase() == b.to_lowercase(),
+    }
+}
+
+/// If `base` is `path` or an ancestor of `path`, comparing whole segments,
+/// returns the remaining segments of `path` joined by forward slashes.
+/// Both paths are expected to be resolved.
+pub fn strip_prefix(path: &str, base: &str, manipulation: FlexPathVariant) -> Option<String> {
+    let (path_root, path_rest) = split_root(path, manipulation);
+    let (base_root, base_rest) = split_root(base, manipulation);
+    if !segment_eq(path_root, base_root, manipulation) {
+        return None;
+    }
+    let mut path_segments = path_rest.split('/').filter(|s| !s.is_empty());
+    for base_segment in base_rest.split('/').filter(|s| !s.is_empty()) {
+        if !path_segments.next().is_some_and(|s| segment_eq(s, base_segment, manipulation)) {
+            return None;
+        }
+    }
+    Some(path_segments.collect::<Vec<&str>>().join("/"))
+}
@@ -0,0 +1,466 @@
+/*!
+Glob patterns matched against `FlexPath`s segment by segment.
+*/
+
+use super::{brace, flexible, BraceError, FlexPath, FlexPathVariant};
+use lazy_regex::*;
+use std::ops::Range;
+
+/// A compiled glob pattern, matched against the segments of a [`FlexPath`].
+///
+/// Syntax:
+///
+/// - `*` matches any sequence of characters within a segment.
+/// - `?` matches a single character within a segment.
+/// - `[abc]`, `[a-z]` and `[!a-z]` (or `[^a-z]`) match a character of a class.
+/// - `**` as a whole segment matches any number of segments, including none.
+/// - `{a,b}` matches any of the alternatives, which may be nested and contain slashes,
+///   and `{1..3}` any number of a range, as expanded by [`expand_braces`](crate::expand_braces).
+/// - A leading `!` negates the pattern.
+/// - A backslash (`\`) escapes the next character. Patterns always delimit
+///   segments with forward slashes (`/`).
+///
+/// A pattern starting with `/`, a drive (`C:/`) or `//` only matches paths with
+/// the same root, while any other pattern only matches relative paths, except for
+/// a pattern starting with `**`, which matches any path. Paths are matched after resolution,
+/// so `C:\src\main.rs` matches `C:/src/*.rs`. Paths of the `Windows` variant
+/// are matched case-insensitively.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexGlob, FlexPath, FlexPathVariant};
+///
+/// let glob = FlexGlob::new("src/**/*.{rs,toml}").unwrap();
+/// assert!(glob.is_match(&FlexPath::new_common("src/lib.rs")));
+/// assert!(glob.is_match(&FlexPath::new_common("src/a/b/Cargo.toml")));
+/// assert!(!glob.is_match(&FlexPath::new_common("tests/lib.rs")));
+/// assert!(glob.is_match(&FlexPath::new(r"SRC\bin\Main.RS", FlexPathVariant::Windows)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlexGlob {
+    pattern: String,
+    negated: bool,
+    pub(crate) alternatives: Vec<GlobAlternative>,
+}
+
+/// A pattern alternative without braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GlobAlternative {
+    pub(crate) root: GlobRoot,
+    pub(crate) segments: Vec<GlobSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GlobRoot {
+    /// A relative pattern.
+    None,
+    /// A pattern starting with `/`.
+    Slash,
+    /// A pattern starting with a drive, such as `C:` or `C:/`.
+    Drive(String),
+    /// A pattern starting with `//`, matching UNC paths.
+    Unc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GlobSegment {
+    /// `**`, matching any number of segments.
+    AnyDepth,
+    Tokens(Vec<GlobToken>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GlobToken {
+    Literal(char),
+    Star,
+    Question,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl FlexGlob {
+    /// Compiles a glob pattern.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pattern has unbalanced braces, an unclosed
+    /// character class, a trailing backslash or too many brace alternatives.
+    pub fn new(pattern: &str) -> Result<Self, GlobError> {
+        let (negated, body) = match pattern.strip_prefix('!') {
+            Some(body) => (true, body),
+            None => (false, pattern),
+        };
+        let alternatives = expand_alternatives(body, pattern)?
+            .iter()
+            .map(|alternative| compile_alternative(alternative, pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { pattern: pattern.to_owned(), negated, alternatives })
+    }
+
+    /// Returns the pattern this glob was compiled from.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Indicates whether the pattern is negated by a leading `!`.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Indicates whether a path matches this glob.
+    pub fn is_match(&self, path: &FlexPath) -> bool {
+        self.alternatives.iter().any(|alternative| alternative.is_match(path)) != self.negated
+    }
+
+    /// Matches a path, returning the text matched by each wildcard
+    /// (`*`, `?`, a character class or `**`) in pattern order. The text
+    /// matched by `**` has its segments joined by forward slashes (`/`).
+    ///
+    /// A negated glob that matches returns no captures.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexGlob, FlexPath};
+    /// let glob = FlexGlob::new("src/**/*.rs").unwrap();
+    /// assert_eq!(Some(vec!["a/b".to_owned(), "c".to_owned()]), glob.captures(&FlexPath::new_common("src/a/b/c.rs")));
+    /// ```
+    pub fn captures(&self, path: &FlexPath) -> Option<Vec<String>> {
+        let captures = self.alternatives.iter().find_map(|alternative| alternative.captures(path));
+        if self.negated {
+            return if captures.is_some() { None } else { Some(vec![]) };
+        }
+        captures
+    }
+}
+
+impl GlobAlternative {
+    pub(crate) fn is_match(&self, path: &FlexPath) -> bool {
+        self.match_segments(path).is_some()
+    }
+
+    pub(crate) fn captures(&self, path: &FlexPath) -> Option<Vec<String>> {
+        let (segments, ranges) = self.match_segments(path)?;
+        let case_insensitive = path.variant() == FlexPathVariant::Windows;
+        let mut captures = vec![];
+        for (pattern, range) in self.segments.iter().zip(ranges) {
+            match pattern {
+                GlobSegment::AnyDepth => captures.push(segments[range].join("/")),
+                GlobSegment::Tokens(tokens) => {
+                    let chars: Vec<char> = segments[range.start].chars().collect();
+                    let spans = match_spans(tokens, &chars, GlobToken::wildcard_min, |token, &c| token.matches_char(c, case_insensitive))
+                        .expect("the segment matched");
+                    for (token, span) in tokens.iter().zip(spans) {
+                        if !matches!(token, GlobToken::Literal(_)) {
+                            captures.push(chars[span].iter().collect());
+                        }
+                    }
+                },
+            }
+        }
+        Some(captures)
+    }
+
+    /// Matches the segments of a path, returning them with the
+    /// segments matched by each pattern segment.
+    fn match_segments<'a>(&self, path: &'a FlexPath) -> Option<(Vec<&'a str>, Vec<Range<usize>>)> {
+        let variant = path.variant();
+        let (root, rest) = path_root(&path.0, variant);
+        // a relative pattern starting with `**` matches any root
+        let root_matches = (self.root == GlobRoot::None && self.segments.first() == Some(&GlobSegment::AnyDepth))
+            || self.root.matches(&root, variant);
+        if !root_matches {
+            return None;
+        }
+        let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+        let case_insensitive = variant == FlexPathVariant::Windows;
+        let ranges = match_spans(&self.segments, &segments, |pattern| (*pattern == GlobSegment::AnyDepth).then_some(0), |pattern, segment| match pattern {
+            GlobSegment::AnyDepth => unreachable!(),
+            GlobSegment::Tokens(tokens) => tokens_match(tokens, &segment.chars().collect::<Vec<_>>(), case_insensitive),
+        })?;
+        Some((segments, ranges))
+    }
+}
+
+impl GlobRoot {
+    /// Indicates whether a pattern root matches the root of a path.
+    pub(crate) fn matches(&self, path_root: &GlobRoot, variant: FlexPathVariant) -> bool {
+        match (self, path_root) {
+            (GlobRoot::Drive(a), GlobRoot::Drive(b)) => flexible::segment_eq(a, b, variant),
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// Splits a resolved path into its root and the remaining segments.
+pub(crate) fn path_root(path: &str, variant: FlexPathVariant) -> (GlobRoot, &str) {
+    let (root, rest) = flexible::split_root(path, variant);
+    let root = match root {
+        "" => GlobRoot::None,
+        "/" => GlobRoot::Slash,
+        r"\\" => GlobRoot::Unc,
+        drive => GlobRoot::Drive(drive.to_owned()),
+    };
+    (root, rest)
+}
+
+impl GlobToken {
+    fn wildcard_min(&self) -> Option<usize> {
+        (*self == GlobToken::Star).then_some(0)
+    }
+
+    fn matches_char(&self, c: char, case_insensitive: bool) -> bool {
+        match self {
+            GlobToken::Literal(literal) => char_eq(*literal, c, case_insensitive),
+            GlobToken::Question | GlobToken::Star => true,
+            GlobToken::Class { negated, ranges } => class_contains(ranges, c, case_insensitive) != *negated,
+        }
+    }
+}
+
+/// Indicates whether the characters of a segment match tokens,
+/// going back only to the most recent `*` when a token does not match.
+fn tokens_match(tokens: &[GlobToken], chars: &[char], case_insensitive: bool) -> bool {
+    let (mut t, mut c) = (0, 0);
+    // the token after the most recent `*` and the character it resumes from
+    let mut resume = None;
+    while c < chars.len() {
+        match tokens.get(t) {
+            Some(GlobToken::Star) => {
+                t += 1;
+                resume = Some((t, c));
+            },
+            Some(token) if token.matches_char(chars[c], case_insensitive) => {
+                t += 1;
+                c += 1;
+            },
+            _ => {
+                let Some((star_t, star_c)) = resume else {
+                    return false;
+                };
+                // let the `*` take one more character
+                resume = Some((star_t, star_c + 1));
+                t = star_t;
+                c = star_c + 1;
+            },
+        }
+    }
+    tokens[t..].iter().all(|token| *token == GlobToken::Star)
+}
+
+/// Matches items against tokens, returning the range of items each token matched.
+///
+/// Tokens for which `wildcard_min` returns a length match at least that many items,
+/// as many as possible from left to right; other tokens match one item for which
+/// `item_matches` holds. This fills a table of which token suffixes match which
+/// item suffixes, so it takes time proportional to the product of their lengths
+/// and calls `item_matches` at most once per pair.
+pub(crate) fn match_spans<T, I>(
+    tokens: &[T],
+    items: &[I],
+    wildcard_min: impl Fn(&T) -> Option<usize>,
+    mut item_matches: impl FnMut(&T, &I) -> bool,
+) -> Option<Vec<Range<usize>>> {
+    let width = items.len() + 1;
+    // matched[i * width + j] indicates whether tokens[i..] match items[j..]
+    let mut matched = vec![false; (tokens.len() + 1) * width];
+    matched[tokens.len() * width + items.len()] = true;
+    for (i, token) in tokens.iter().enumerate().rev() {
+        let next = (i + 1) * width;
+        match wildcard_min(token) {
+            Some(min) => {
+                let mut any_later = false;
+                for j in (0..width).rev() {
+                    if j + min < width {
+                        any_later |= matched[next + j + min];
+                    }
+                    matched[i * width + j] = any_later;
+                }
+            },
+            None => {
+                for j in 0..items.len() {
+                    matched[i * width + j] = matched[next + j + 1] && item_matches(token, &items[j]);
+                }
+            },
+        }
+    }
+    if !matched[0] {
+        return None;
+    }
+    let mut spans = Vec::with_capacity(tokens.len());
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        let next = (i + 1) * width;
+        let end = match wildcard_min(token) {
+            Some(min) => (start + min..width).rev().find(|&end| matched[next + end]).unwrap(),
+            None => start + 1,
+        };
+        spans.push(start..end);
+        start = end;
+    }
+    Some(spans)
+}
+
+pub(crate) fn char_eq(a: char, b: char, case_insensitive: bool) -> bool {
+    a == b || (case_insensitive && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+fn class_contains(ranges: &[(char, char)], c: char, case_insensitive: bool) -> bool {
+    let contains = |c: char| ranges.iter().any(|&(from, to)| from <= c && c <= to);
+    contains(c) || (case_insensitive && c.to_lowercase().chain(c.to_uppercase()).any(contains))
+}
+
+/// Expands brace alternations and ranges, keeping escapes for the compilation step.
+fn expand_alternatives(pattern: &str, full_pattern: &str) -> Result<Vec<String>, GlobError> {
+    let trailing_backslashes = pattern.chars().rev().take_while(|&c| c == '\\').count();
+    if trailing_backslashes % 2 == 1 {
+        return Err(GlobError::TrailingEscape(full_pattern.to_owned()));
+    }
+    brace::expand_strings(pattern, brace::Escapes::Keep, brace::DEFAULT_BRACE_EXPANSION_LIMIT).map_err(|error| match error {
+        BraceError::Unbalanced(_) => GlobError::UnbalancedBraces(full_pattern.to_owned()),
+        BraceError::TooManyResults { .. } => GlobError::TooManyAlternatives(full_pattern.to_owned()),
+    })
+}
+
+/// Splits a pattern into its root and the remaining text.
+pub(crate) fn pattern_root(pattern: &str) -> (GlobRoot, &str) {
+    if let Some(rest) = pattern.strip_prefix("//") {
+        (GlobRoot::Unc, rest)
+    } else if let Some(rest) = pattern.strip_prefix('/') {
+        (GlobRoot::Slash, rest)
+    } else if regex_is_match!(r"^[A-Za-z]:", pattern) {
+        let root_len = if pattern[2..].starts_with('/') { 3 } else { 2 };
+        (GlobRoot::Drive(pattern[..root_len].to_owned()), &pattern[root_len..])
+    } else {
+        (GlobRoot::None, pattern)
+    }
+}
+
+/// Compiles a pattern alternative without braces.
+fn compile_alternative(pattern: &str, full_pattern: &str) -> Result<GlobAlternative, GlobError> {
+    let (root, rest) = pattern_root(pattern);
+    let segments = split_pattern_segments(rest)
+        .into_iter()
+        .filter(|s| !s.is_empty() && s != ".")
+        .map(|s| if s == "**" { Ok(GlobSegment::AnyDepth) } else { compile_tokens(&s, full_pattern).map(GlobSegment::Tokens) })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(GlobAlternative { root, segments })
+}
+
+/// Splits a pattern at unescaped forward slashes.
+fn split_pattern_segments(pattern: &str) -> Vec<String> {
+    let mut r = vec![String::new()];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' => r.push(String::new()),
+            '\\' => {
+                let last = r.last_mut().unwrap();
+                last.push('\\');
+                last.extend(chars.next());
+            },
+            _ => r.last_mut().unwrap().push(c),
+        }
+    }
+    r
+}
+
+fn compile_tokens(segment: &str, full_pattern: &str) -> Result<Vec<GlobToken>, GlobError> {
+    let chars: Vec<char> = segment.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                tokens.push(GlobToken::Literal(chars[i + 1]));
+                i += 2;
+            },
+            '*' => {
+                // consecutive asterisks within a segment act as one
+                if tokens.last() != Some(&GlobToken::Star) {
+                    tokens.push(GlobToken::Star);
+                }
+                i += 1;
+            },
+            '?' => {
+                tokens.push(GlobToken::Question);
+                i += 1;
+            },
+            '[' => {
+                let (token, end) = compile_class(&chars, i + 1).ok_or_else(|| GlobError::UnclosedClass(full_pattern.to_owned()))?;
+                tokens.push(token);
+                i = end;
+            },
+            c => {
+                tokens.push(GlobToken::Literal(c));
+                i += 1;
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+/// Compiles a character class starting after its opening bracket,
+/// returning the class and the index after its closing bracket.
+fn compile_class(chars: &[char], start: usize) -> Option<(GlobToken, usize)> {
+    let mut i = start;
+    let negated = matches!(chars.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+    let mut ranges = vec![];
+    let mut first = true;
+    loop {
+        let mut c = *chars.get(i)?;
+        if c == ']' && !first {
+            return Some((GlobToken::Class { negated, ranges }, i + 1));
+        }
+        first = false;
+        if c == '\\' {
+            i += 1;
+            c = *chars.get(i)?;
+        }
+        i += 1;
+        if chars.get(i) == Some(&'-') && chars.get(i + 1).is_some_and(|&next| next != ']') {
+            let mut to = chars[i + 1];
+            i += 2;
+            if to == '\\' {
+                to = *chars.get(i)?;
+                i += 1;
+            }
+            ranges.push((c, to));
+        } else {
+            ranges.push((c, c));
+        }
+    }
+}
+
+/// An error returned from [`FlexGlob::new`], holding the pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobError {
+    /// The pattern has an unclosed brace or a closing brace without an opening brace.
+    UnbalancedBraces(String),
+    /// The pattern has an unclosed character class.
+    UnclosedClass(String),
+    /// The pattern ends with a backslash.
+    TrailingEscape(String),
+    /// The pattern's braces expand to more alternatives than
+    /// [`DEFAULT_BRACE_EXPANSION_LIMIT`](crate::DEFAULT_BRACE_EXPANSION_LIMIT).
+    TooManyAlternatives(String),
+}
+
+impl std::fmt::Display for GlobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnbalancedBraces(pattern) => write!(f, "unbalanced braces in glob {:?}", pattern),
+            Self::UnclosedClass(pattern) => write!(f, "unclosed character class in glob {:?}", pattern),
+            Self::TrailingEscape(pattern) => write!(f, "trailing backslash in glob {:?}", pattern),
+            Self::TooManyAlternatives(pattern) => write!(f, "too many brace alternatives in glob {:?}", pattern),
+        }
+    }
+}
+
+impl std::error::Error for GlobError {}
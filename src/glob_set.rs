@@ -0,0 +1,205 @@
+/*!
+Matching of a path against many glob patterns at once.
+*/
+
+use super::{FlexGlob, FlexPath};
+use super::glob::{path_root, GlobAlternative, GlobRoot, GlobSegment, GlobToken};
+use std::collections::HashMap;
+
+/// A set of [`FlexGlob`]s matched against a path at once.
+///
+/// Patterns are indexed by their literal parts, such as a literal path,
+/// a literal file name, an extension (as in `*.rs`) or a literal first segment,
+/// so that matching a path only runs the patterns that could match it.
+/// Patterns without such literal parts, and negated patterns, are always run.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexGlob, FlexGlobSet, FlexPath};
+///
+/// let mut set = FlexGlobSet::new();
+/// set.add(FlexGlob::new("**/*.rs").unwrap());
+/// set.add(FlexGlob::new("docs/**").unwrap());
+/// set.add(FlexGlob::new("src/main.rs").unwrap());
+/// assert_eq!(vec![0, 2], set.matches(&FlexPath::new_common("src/main.rs")));
+/// assert_eq!(vec![1], set.matches(&FlexPath::new_common("docs/index.md")));
+/// assert!(!set.is_match(&FlexPath::new_common("README.md")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FlexGlobSet {
+    globs: Vec<FlexGlob>,
+    literal_paths: HashMap<String, Vec<Candidate>>,
+    base_names: HashMap<String, Vec<Candidate>>,
+    extensions: HashMap<String, Vec<Candidate>>,
+    first_segments: HashMap<String, Vec<Candidate>>,
+    fallback: Vec<Candidate>,
+}
+
+/// A glob to run against a path: either one of its alternatives,
+/// or the whole glob if `alternative` is `None`.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    glob: usize,
+    alternative: Option<usize>,
+}
+
+impl FlexGlobSet {
+    /// Constructs an empty `FlexGlobSet`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a glob, returning its index.
+    pub fn add(&mut self, glob: FlexGlob) -> usize {
+        let index = self.globs.len();
+        if glob.is_negated() {
+            self.fallback.push(Candidate { glob: index, alternative: None });
+        } else {
+            for (i, alternative) in glob.alternatives.iter().enumerate() {
+                let candidate = Candidate { glob: index, alternative: Some(i) };
+                let (map, key) = match index_key(alternative) {
+                    Some(IndexKey::LiteralPath(key)) => (&mut self.literal_paths, key),
+                    Some(IndexKey::BaseName(key)) => (&mut self.base_names, key),
+                    Some(IndexKey::Extension(key)) => (&mut self.extensions, key),
+                    Some(IndexKey::FirstSegment(key)) => (&mut self.first_segments, key),
+                    None => {
+                        self.fallback.push(candidate);
+                        continue;
+                    },
+                };
+                map.entry(key).or_default().push(candidate);
+            }
+        }
+        self.globs.push(glob);
+        index
+    }
+
+    /// Returns the globs, in insertion order.
+    pub fn globs(&self) -> &[FlexGlob] {
+        &self.globs
+    }
+
+    /// Returns the number of globs.
+    pub fn len(&self) -> usize {
+        self.globs.len()
+    }
+
+    /// Indicates whether the set has no globs.
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty()
+    }
+
+    /// Returns the indices of every glob matching a path, in ascending order.
+    pub fn matches(&self, path: &FlexPath) -> Vec<usize> {
+        let mut matched = vec![false; self.globs.len()];
+        for candidate in self.candidates(path) {
+            if !matched[candidate.glob] && self.run(candidate, path) {
+                matched[candidate.glob] = true;
+            }
+        }
+        matched.iter().enumerate().filter(|(_, &m)| m).map(|(i, _)| i).collect()
+    }
+
+    /// Indicates whether any glob matches a path.
+    pub fn is_match(&self, path: &FlexPath) -> bool {
+        self.candidates(path).any(|candidate| self.run(candidate, path))
+    }
+
+    fn run(&self, candidate: Candidate, path: &FlexPath) -> bool {
+        let glob = &self.globs[candidate.glob];
+        match candidate.alternative {
+            Some(i) => glob.alternatives[i].captures(path).is_some(),
+            None => glob.is_match(path),
+        }
+    }
+
+    fn candidates<'a>(&'a self, path: &FlexPath) -> impl Iterator<Item = Candidate> + 'a {
+        let (root, rest) = path_root(&path.0, path.variant());
+        let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+        let root = root_key(&root);
+        let base_name = segments.last().map(|s| fold(s));
+
+        let mut keys: Vec<(&HashMap<String, Vec<Candidate>>, String)> = vec![
+            (&self.literal_paths, root.clone() + &fold(&segments.join("/"))),
+        ];
+        if let Some(base_name) = base_name {
+            if let Some(i) = base_name.rfind('.') {
+                keys.push((&self.extensions, base_name[i + 1..].to_owned()));
+            }
+            keys.push((&self.base_names, base_name));
+        }
+        if let Some(first) = segments.first() {
+            keys.push((&self.first_segments, root + &fold(first)));
+        }
+        keys.into_iter()
+            .filter_map(|(map, key)| map.get(&key))
+            .flatten()
+            .chain(self.fallback.iter())
+            .copied()
+    }
+}
+
+enum IndexKey {
+    LiteralPath(String),
+    BaseName(String),
+    Extension(String),
+    FirstSegment(String),
+}
+
+/// Finds the most selective key under which a pattern alternative is indexed.
+fn index_key(alternative: &GlobAlternative) -> Option<IndexKey> {
+    let literals: Option<Vec<String>> = alternative.segments.iter().map(literal_segment).collect();
+    if let Some(literals) = literals {
+        return Some(IndexKey::LiteralPath(root_key(&alternative.root) + &fold(&literals.join("/"))));
+    }
+    if let Some(GlobSegment::Tokens(tokens)) = alternative.segments.last() {
+        if let Some(base_name) = literal_segment(alternative.segments.last().unwrap()) {
+            return Some(IndexKey::BaseName(fold(&base_name)));
+        }
+        // the literal characters after the last wildcard end every matching file name
+        let suffix: String = tokens.iter().rev()
+            .map_while(|token| if let GlobToken::Literal(c) = token { Some(*c) } else { None })
+            .collect::<Vec<char>>()
+            .into_iter()
+            .rev()
+            .collect();
+        if let Some(i) = suffix.rfind('.') {
+            return Some(IndexKey::Extension(fold(&suffix[i + 1..])));
+        }
+    }
+    // a relative pattern starting with `**` may match any root
+    let any_root = alternative.root == GlobRoot::None && alternative.segments.first() == Some(&GlobSegment::AnyDepth);
+    match alternative.segments.first().and_then(literal_segment) {
+        Some(first) if !any_root => Some(IndexKey::FirstSegment(root_key(&alternative.root) + &fold(&first))),
+        _ => None,
+    }
+}
+
+/// Returns the text of a segment consisting only of literal characters.
+fn literal_segment(segment: &GlobSegment) -> Option<String> {
+    match segment {
+        GlobSegment::Tokens(tokens) => tokens.iter().map(|token| match token {
+            GlobToken::Literal(c) => Some(*c),
+            _ => None,
+        }).collect(),
+        GlobSegment::AnyDepth => None,
+    }
+}
+
+/// Returns a key identifying a root, followed by a separator
+/// that cannot occur in a segment.
+fn root_key(root: &GlobRoot) -> String {
+    match root {
+        GlobRoot::None => "\0".into(),
+        GlobRoot::Slash => "/\0".into(),
+        GlobRoot::Unc => "//\0".into(),
+        GlobRoot::Drive(drive) => fold(drive) + "\0",
+    }
+}
+
+/// Folds the case of a key, character by character, so that keys
+/// agree with the case-insensitive comparison of the `Windows` variant.
+fn fold(s: &str) -> String {
+    s.chars().flat_map(char::to_lowercase).collect()
+}
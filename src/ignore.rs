@@ -0,0 +1,229 @@
+/*!
+Ignore files following `.gitignore` semantics.
+*/
+
+use super::{flexible, FlexGlob, FlexPath};
+
+/// The rules of an ignore file, such as a `.gitignore` file.
+///
+/// Each non-blank line that does not start with `#` is a rule:
+///
+/// - A rule with a slash at its start or middle is anchored to the directory
+///   of the ignore file. Any other rule matches at any depth.
+/// - A trailing slash (`/`) only matches directories.
+/// - A leading `!` re-includes paths excluded by an earlier rule.
+/// - `*`, `?`, `[a-z]` and `**` are wildcards, as in [`FlexGlob`], except that
+///   braces are literal. A trailing `/**` matches everything inside a directory.
+/// - A backslash (`\`) escapes the next character, such as a leading `#` or `!`
+///   or a trailing space. Unescaped trailing spaces are ignored.
+///
+/// Lines with invalid patterns, such as an unclosed character class, are skipped.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::IgnoreFile;
+///
+/// let file = IgnoreFile::parse("# build output\n/target\n*.log\n!keep.log\n");
+/// assert_eq!(3, file.rules().len());
+/// assert!(file.rules()[0].is_anchored());
+/// assert!(file.rules()[2].is_negated());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IgnoreFile {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    /// Parses the text of an ignore file.
+    pub fn parse(text: &str) -> Self {
+        let rules = text.lines().enumerate().filter_map(|(i, line)| IgnoreRule::parse(line, i + 1)).collect();
+        Self { rules }
+    }
+
+    /// Returns the rules, in file order.
+    pub fn rules(&self) -> &[IgnoreRule] {
+        &self.rules
+    }
+}
+
+/// A rule of an [`IgnoreFile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRule {
+    pattern: String,
+    line: usize,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+    glob: FlexGlob,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str, line_number: usize) -> Option<Self> {
+        if line.starts_with('#') {
+            return None;
+        }
+        let mut body = trim_unescaped_trailing_spaces(line);
+        let negated = body.starts_with('!');
+        if negated {
+            body = &body[1..];
+        }
+        let dir_only = body.ends_with('/');
+        if dir_only {
+            body = body.trim_end_matches('/');
+        }
+        let anchored = body.contains('/');
+        body = body.strip_prefix('/').unwrap_or(body);
+        if body.is_empty() {
+            return None;
+        }
+
+        // braces are literal in ignore files
+        let mut glob_pattern = String::new();
+        let mut chars = body.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    glob_pattern.push('\\');
+                    glob_pattern.extend(chars.next());
+                },
+                '{' | '}' => {
+                    glob_pattern.push('\\');
+                    glob_pattern.push(c);
+                },
+                _ => glob_pattern.push(c),
+            }
+        }
+        if glob_pattern.starts_with('!') {
+            glob_pattern.insert(0, '\\');
+        }
+        // a trailing `/**` matches what is inside a directory, but not the directory itself
+        if glob_pattern.ends_with("/**") {
+            glob_pattern.push_str("/*");
+        }
+        if !anchored {
+            glob_pattern.insert_str(0, "**/");
+        }
+        let glob = FlexGlob::new(&glob_pattern).ok()?;
+        Some(Self { pattern: line.to_owned(), line: line_number, negated, dir_only, anchored, glob })
+    }
+
+    /// Returns the line of the rule, as written in the ignore file.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the line number of the rule, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Indicates whether the rule re-includes paths, as with a leading `!`.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Indicates whether the rule only matches directories, as with a trailing `/`.
+    pub fn is_dir_only(&self) -> bool {
+        self.dir_only
+    }
+
+    /// Indicates whether the rule is anchored to the directory of its ignore file.
+    pub fn is_anchored(&self) -> bool {
+        self.anchored
+    }
+
+    fn is_match(&self, relative_path: &FlexPath, is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && self.glob.is_match(relative_path)
+    }
+}
+
+fn trim_unescaped_trailing_spaces(line: &str) -> &str {
+    let mut body = line;
+    while let Some(rest) = body.strip_suffix(' ') {
+        let backslashes = rest.chars().rev().take_while(|&c| c == '\\').count();
+        if backslashes % 2 == 1 {
+            break;
+        }
+        body = rest;
+    }
+    body
+}
+
+/// Decides whether paths are ignored according to ignore files
+/// in several directories.
+///
+/// A rule of an ignore file applies to the paths under its directory.
+/// The last matching rule decides, and rules of ignore files in deeper
+/// directories take priority over rules of ignore files in shallower directories.
+/// A path inside an ignored directory is always ignored, even if a rule re-includes it.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPath, IgnoreFile, IgnoreMatcher};
+///
+/// let mut matcher = IgnoreMatcher::new();
+/// matcher.add(FlexPath::new_common("/repo"), IgnoreFile::parse("*.log\nbuild/\n"));
+/// matcher.add(FlexPath::new_common("/repo/app"), IgnoreFile::parse("!debug.log\n"));
+/// assert!(matcher.is_ignored(&FlexPath::new_common("/repo/error.log"), false));
+/// assert!(!matcher.is_ignored(&FlexPath::new_common("/repo/app/debug.log"), false));
+/// assert!(matcher.is_ignored(&FlexPath::new_common("/repo/app/build/main.o"), false));
+/// assert!(!matcher.is_ignored(&FlexPath::new_common("/repo/build"), false));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreMatcher {
+    /// Ignore files by their directory, from the shallowest directory.
+    files: Vec<(FlexPath, IgnoreFile)>,
+}
+
+impl IgnoreMatcher {
+    /// Constructs an `IgnoreMatcher` without ignore files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the ignore file of a directory. If a directory has several
+    /// ignore files, the rules of the last added file come last.
+    pub fn add(&mut self, dir: FlexPath, file: IgnoreFile) {
+        let dir_depth = depth(&dir);
+        let index = self.files.partition_point(|(other, _)| depth(other) <= dir_depth);
+        self.files.insert(index, (dir, file));
+    }
+
+    /// Indicates whether a path is ignored. `is_dir` indicates whether
+    /// the path is a directory, for rules with a trailing slash.
+    pub fn is_ignored(&self, path: &FlexPath, is_dir: bool) -> bool {
+        self.deciding_rule(path, is_dir).is_some_and(|(_, rule)| !rule.is_negated())
+    }
+
+    /// Returns the rule deciding whether a path is ignored, with the directory
+    /// of its ignore file. This is the rule ignoring an ancestor directory
+    /// if there is one.
+    pub fn deciding_rule(&self, path: &FlexPath, is_dir: bool) -> Option<(&FlexPath, &IgnoreRule)> {
+        let (dir, _) = self.files.iter().find(|(dir, _)| path.starts_with(dir))?;
+        let relative = path.strip_prefix(dir).unwrap();
+        let segments: Vec<&str> = relative.split('/').filter(|s| !s.is_empty()).collect();
+        for i in 1..segments.len() {
+            let ancestor = dir.resolve(&segments[..i].join("/"));
+            if let Some((dir, rule)) = self.last_matching_rule(&ancestor, true) {
+                if !rule.is_negated() {
+                    return Some((dir, rule));
+                }
+            }
+        }
+        self.last_matching_rule(path, is_dir)
+    }
+
+    fn last_matching_rule(&self, path: &FlexPath, is_dir: bool) -> Option<(&FlexPath, &IgnoreRule)> {
+        self.files.iter().rev().find_map(|(dir, file)| {
+            let relative = flexible::strip_prefix(&path.0, &dir.0, path.1).filter(|relative| !relative.is_empty())?;
+            let relative = FlexPath::new(&relative, path.1);
+            file.rules.iter().rev().find(|rule| rule.is_match(&relative, is_dir)).map(|rule| (dir, rule))
+        })
+    }
+}
+
+fn depth(dir: &FlexPath) -> usize {
+    flexible::split_root(&dir.0, dir.1).1.split('/').filter(|s| !s.is_empty()).count()
+}
@@ -0,0 +1,89 @@
+/*!
+Joining untrusted paths without escaping a root.
+*/
+
+use super::{FlexPath, FlexPathVariant};
+use lazy_regex::*;
+
+impl FlexPath {
+    /// Joins an untrusted relative path to this path, refusing
+    /// any input that would lead outside this path.
+    ///
+    /// Unlike [`.resolve`], which lets an absolute input replace the base
+    /// and collapses `..` segments lexically, this rejects:
+    ///
+    /// - inputs starting with a separator, such as `/etc/passwd` or `\Windows`;
+    /// - for the `Windows` variant, inputs starting with a drive, such as `C:\` or `C:x`;
+    /// - inputs starting with a UNC or device prefix, such as `\\server\share` or `\\?\`;
+    /// - inputs whose `..` segments go above this path at any point,
+    ///   such as `../x` or `a/../../x`, even if they come back into it.
+    ///
+    /// Both forward slashes and backslashes are separators, as in [`.resolve`].
+    ///
+    /// [`.resolve`]: FlexPath::resolve
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{EscapeError, FlexPath, FlexPathVariant};
+    ///
+    /// let root = FlexPath::new_common("/srv/uploads");
+    /// assert_eq!("/srv/uploads/a/b.png", root.join_within("a/./c/../b.png").unwrap().to_string());
+    /// assert_eq!(Err(EscapeError::LeavesRoot("../../etc/passwd".into())), root.join_within("../../etc/passwd"));
+    /// assert_eq!(Err(EscapeError::Absolute("/etc/passwd".into())), root.join_within("/etc/passwd"));
+    ///
+    /// let root = FlexPath::new(r"C:\uploads", FlexPathVariant::Windows);
+    /// assert_eq!(Err(EscapeError::Prefix(r"D:\x".into())), root.join_within(r"D:\x"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if the input is absolute, has a drive or UNC prefix, or leaves this path.
+    pub fn join_within(&self, untrusted: &str) -> Result<FlexPath, EscapeError> {
+        if regex_is_match!(r"^[/\\]{2}", untrusted)
+            || (self.1 == FlexPathVariant::Windows && regex_is_match!(r"^[A-Za-z]:", untrusted)) {
+            return Err(EscapeError::Prefix(untrusted.to_owned()));
+        }
+        if regex_is_match!(r"^[/\\]", untrusted) {
+            return Err(EscapeError::Absolute(untrusted.to_owned()));
+        }
+        let mut depth: usize = 0;
+        for segment in untrusted.split(['/', '\\']) {
+            match segment {
+                "" | "." => {},
+                ".." => {
+                    depth = depth.checked_sub(1).ok_or_else(|| EscapeError::LeavesRoot(untrusted.to_owned()))?;
+                },
+                _ => depth += 1,
+            }
+        }
+        let joined = self.resolve(untrusted);
+        if !joined.starts_with(self) {
+            return Err(EscapeError::LeavesRoot(untrusted.to_owned()));
+        }
+        Ok(joined)
+    }
+}
+
+/// An error returned from [`FlexPath::join_within`], holding the untrusted input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeError {
+    /// The input starts with a separator.
+    Absolute(String),
+    /// The input starts with a drive, UNC or device prefix.
+    Prefix(String),
+    /// The input goes above the root through `..` segments.
+    LeavesRoot(String),
+}
+
+impl std::fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Absolute(path) => write!(f, "{:?} is absolute", path),
+            Self::Prefix(path) => write!(f, "{:?} has a drive or UNC prefix", path),
+            Self::LeavesRoot(path) => write!(f, "{:?} leaves the root directory", path),
+        }
+    }
+}
+
+impl std::error::Error for EscapeError {}
@@ -16,6 +16,14 @@ The constant `FlexPathVariant::NATIVE` is one of these variants
 based on the target platform. For the Windows operating system, it
 is always _Windows_. For other platforms, it's always _Common_.
 
+# Conformance
+
+[`normalize_with`] and [`relative_with`] reproduce the output of the Node.js,
+Python or Go standard libraries, as selected by a [`Conformance`]. There is no
+`FlexPath::new_with`: a `FlexPath` is always resolved, while these ecosystems keep
+leading `..` segments, trailing separators or a `.` for an empty path, so their
+normalized output is returned as text.
+
 # Example
 
 ```
@@ -33,6 +41,53 @@ use lazy_regex::*;
 pub(crate) mod common;
 pub(crate) mod flexible;
 
+mod mount;
+pub use mount::*;
+
+mod extensions;
+pub use extensions::*;
+
+mod pathlib;
+pub use pathlib::*;
+
+mod parsed;
+pub use parsed::*;
+
+mod conformance;
+pub use conformance::*;
+
+mod brace;
+pub use brace::*;
+mod glob;
+pub use glob::*;
+mod glob_set;
+pub use glob_set::*;
+mod ignore;
+pub use ignore::*;
+mod path_pattern;
+pub use path_pattern::*;
+mod rewrite;
+pub use rewrite::*;
+mod tree_mapping;
+pub use tree_mapping::*;
+mod rename;
+pub use rename::*;
+mod access;
+pub use access::*;
+mod join;
+pub use join::*;
+mod archive;
+pub use archive::*;
+mod object_key;
+pub use object_key::*;
+mod url;
+pub use url::*;
+
+#[cfg(feature = "mime")]
+mod mime;
+#[cfg(feature = "mime")]
+pub use mime::*;
+
 /// Indicates if special absolute paths are considered.
 ///
 /// Currently, only two variants are defined, considering that there is
@@ -139,14 +194,19 @@ impl FlexPath {
     # Behavior:
 
     - If the paths refer to the same path, this function returns
-    an empty string.
-    - The function ensures that both paths are absolute and resolves
-    any `..` and `.` segments inside.
-    - If both paths have different prefix, `to_path` is returned.
+      an empty string.
+    - The function resolves any `..` and `.` segments inside both paths.
+    - If both paths are relative, they are compared as if both were
+      under the same base directory, producing leading `..` segments where needed.
+      Leading `..` segments of `to_path` are kept.
+    - Segments of the `Windows` variant are compared case-insensitively,
+      as in [`.starts_with`].
+    - If both paths have different prefix, `to_path` is returned resolved.
+      Use [`.relative_outcome`] to tell this case apart.
 
     # Panics
 
-    Panics if given paths are not absolute.
+    Panics if one of the given paths is absolute and the other is relative.
 
     # Example
 
@@ -156,12 +216,131 @@ impl FlexPath {
     assert_eq!("c", FlexPath::new_common("/a/b").relative("/a/b/c"));
     assert_eq!("../../c/d", FlexPath::new_common("/a/b").relative("/c/d"));
     assert_eq!("../c", FlexPath::new_common("/a/b").relative("/a/c"));
+    assert_eq!("../../c", FlexPath::new_common("a/b").relative("c"));
     ```
     */
     pub fn relative(&self, to_path: &str) -> String {
         flexible::relative(&self.0, to_path, self.1)
     }
 
+    /// Finds the relative path from this path to `to_path`, as in [`.relative`],
+    /// but explicitly indicates when both paths have different prefixes,
+    /// such as different drives or different UNC shares.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the given paths is absolute and the other is relative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexPath, FlexPathVariant, RelativeOutcome};
+    /// let windows = FlexPathVariant::Windows;
+    /// assert_eq!(RelativeOutcome::Relative("../b".into()), FlexPath::new("C:/a", windows).relative_outcome("C:/b"));
+    /// assert_eq!(RelativeOutcome::Unrelated(FlexPath::new("D:/b", windows)), FlexPath::new("C:/a", windows).relative_outcome("D:/b"));
+    /// ```
+    pub fn relative_outcome(&self, to_path: &str) -> RelativeOutcome {
+        match flexible::try_relative(&self.0, to_path, self.1) {
+            Some(path) => RelativeOutcome::Relative(path),
+            None => RelativeOutcome::Unrelated(FlexPath::new(to_path, self.1)),
+        }
+    }
+
+    /// Computes the text of a symbolic link located at this path and
+    /// pointing to `target`.
+    ///
+    /// The text is relative to the directory containing the link, not to the
+    /// link itself. If both paths have different prefixes, the text is the absolute `target`.
+    /// For the `Windows` variant, the text is delimited by backslashes,
+    /// and `target_is_dir` indicates whether a directory symbolic link is to be created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the given paths is absolute and the other is relative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexPath, FlexPathVariant, SymlinkKind};
+    /// let link = FlexPath::new_common("/a/b/link");
+    /// assert_eq!("../c/file", link.symlink_target_to(&FlexPath::new_common("/a/c/file"), false).text());
+    ///
+    /// let link = FlexPath::new("C:/a/b/link", FlexPathVariant::Windows);
+    /// let target = link.symlink_target_to(&FlexPath::new("C:/a/c", FlexPathVariant::Windows), true);
+    /// assert_eq!(r"..\c", target.text());
+    /// assert_eq!(SymlinkKind::Directory, target.kind());
+    /// ```
+    pub fn symlink_target_to(&self, target: &FlexPath, target_is_dir: bool) -> SymlinkTarget {
+        let path = match self.resolve("..").relative_outcome(&target.0) {
+            RelativeOutcome::Relative(path) if path.is_empty() => FlexPath(".".into(), self.1),
+            RelativeOutcome::Relative(path) => FlexPath(path, self.1),
+            RelativeOutcome::Unrelated(path) => path,
+        };
+        SymlinkTarget {
+            text: path.to_string_with_flex_separator(),
+            kind: if target_is_dir { SymlinkKind::Directory } else { SymlinkKind::File },
+        }
+    }
+
+    /// Indicates whether `base` is this path or one of its ancestors.
+    ///
+    /// Paths are compared by whole segments, so `/a/bc` does not start with `/a/b`.
+    /// Segments are compared case-insensitively for the `Windows` variant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert!(FlexPath::new_common("/a/b/c").starts_with(&FlexPath::new_common("/a/b")));
+    /// assert!(!FlexPath::new_common("/a/bc").starts_with(&FlexPath::new_common("/a/b")));
+    /// ```
+    pub fn starts_with(&self, base: &FlexPath) -> bool {
+        flexible::strip_prefix(&self.0, &base.0, self.1).is_some()
+    }
+
+    /// Returns the segments of this path that follow `base`, joined by
+    /// forward slashes. This is an empty string if both paths are equal.
+    ///
+    /// Paths are compared as in [`.starts_with`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `base` is not this path or one of its ancestors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!("c/d", FlexPath::new_common("/a/b/c/d").strip_prefix(&FlexPath::new_common("/a/b")).unwrap());
+    /// assert!(FlexPath::new_common("/a/b").strip_prefix(&FlexPath::new_common("/c")).is_err());
+    /// ```
+    pub fn strip_prefix(&self, base: &FlexPath) -> Result<String, StripPrefixError> {
+        flexible::strip_prefix(&self.0, &base.0, self.1).ok_or(StripPrefixError(()))
+    }
+
+    /// Moves this path from under `from_root` to under `to_root`,
+    /// keeping the segments that follow `from_root`. The result has
+    /// the variant of `to_root`.
+    ///
+    /// Both absolute and relative paths are accepted, and
+    /// paths are compared as in [`.starts_with`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if `from_root` is not this path or one of its ancestors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// let path = FlexPath::new_common("src/a/b.rs");
+    /// assert_eq!("out/a/b.rs", path.rebase(&FlexPath::new_common("src"), &FlexPath::new_common("out")).unwrap().to_string());
+    /// assert!(path.rebase(&FlexPath::new_common("lib"), &FlexPath::new_common("out")).is_err());
+    /// ```
+    pub fn rebase(&self, from_root: &FlexPath, to_root: &FlexPath) -> Result<FlexPath, StripPrefixError> {
+        Ok(to_root.resolve(&self.strip_prefix(from_root)?))
+    }
+
     /// Changes the extension of a path and returns a new string.
     /// This method adds any lacking dot (`.`) prefix automatically to the
     /// `extension` argument.
@@ -169,6 +348,10 @@ impl FlexPath {
     /// This method allows multiple dots per extension. If that is not
     /// desired, use [`.change_last_extension`].
     ///
+    /// Extensions are only looked up in the file name, and leading dots
+    /// belong to the file name, so `.bashrc` has no extension
+    /// and `.bashrc.bak` has the extension `.bak`.
+    ///
     /// # Example
     /// 
     /// ```
@@ -176,6 +359,8 @@ impl FlexPath {
     /// assert_eq!("a.y", FlexPath::new_common("a.x").change_extension(".y").to_string());
     /// assert_eq!("a.z", FlexPath::new_common("a.x.y").change_extension(".z").to_string());
     /// assert_eq!("a.z.w", FlexPath::new_common("a.x.y").change_extension(".z.w").to_string());
+    /// assert_eq!("a.b/c.x", FlexPath::new_common("a.b/c").change_extension("x").to_string());
+    /// assert_eq!(".bashrc.x", FlexPath::new_common(".bashrc").change_extension("x").to_string());
     /// ```
     ///
     pub fn change_extension(&self, extension: &str) -> FlexPath {
@@ -186,26 +371,164 @@ impl FlexPath {
     /// This method adds any lacking dot (`.`) prefix automatically to the
     /// `extension` argument.
     ///
+    /// Extensions are looked up as in [`.change_extension`]. The built-in compound
+    /// extensions of [`CompoundExtensions::with_defaults`] count as a single extension,
+    /// so `lib.d.ts` becomes `lib.js`; [`.change_last_extension_with`] takes
+    /// another registry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!("a.x.z", FlexPath::new_common("a.x.y").change_last_extension("z").to_string());
+    /// assert_eq!(".bashrc.z", FlexPath::new_common(".bashrc").change_last_extension("z").to_string());
+    /// assert_eq!("lib.js", FlexPath::new_common("lib.d.ts").change_last_extension("js").to_string());
+    /// ```
+    ///
     /// # Panics
     ///
-    /// Panics if the extension contains more than one dot.
+    /// Panics if the extension contains more than one dot and
+    /// is not a built-in compound extension.
     ///
     pub fn change_last_extension(&self, extension: &str) -> FlexPath {
-        Self(change_last_extension(&self.0, extension), self.1)
+        self.change_last_extension_with(extension, &DEFAULT_COMPOUND_EXTENSIONS)
+    }
+
+    /// Changes only the last extension of a path and returns a new string,
+    /// treating registered compound extensions as a single extension.
+    /// Compound extensions are compared as in [`ExtensionMatching::for_variant`].
+    /// This method adds any lacking dot (`.`) prefix automatically to the
+    /// `extension` argument.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{CompoundExtensions, FlexPath};
+    /// let compound = CompoundExtensions::with_defaults();
+    /// assert_eq!("lib.js", FlexPath::new_common("lib.d.ts").change_last_extension_with("js", &compound).to_string());
+    /// assert_eq!("lib.d.mts", FlexPath::new_common("lib.d.ts").change_last_extension_with("d.mts", &compound).to_string());
+    /// assert_eq!("a.b.js", FlexPath::new_common("a.b.ts").change_last_extension_with("js", &compound).to_string());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extension contains more than one dot and
+    /// is not a registered compound extension.
+    ///
+    pub fn change_last_extension_with(&self, extension: &str, compound: &CompoundExtensions) -> FlexPath {
+        Self(change_last_extension_with(&self.0, extension, compound, ExtensionMatching::for_variant(self.1)), self.1)
+    }
+
+    /// Returns every extension of the file name, without dot prefixes.
+    /// Extensions are looked up as in [`.change_extension`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!(vec!["tar", "gz"], FlexPath::new_common("a/b.tar.gz").extensions());
+    /// assert!(FlexPath::new_common(".bashrc").extensions().is_empty());
+    /// ```
+    pub fn extensions(&self) -> Vec<String> {
+        let name = split_base_name(&self.0).1;
+        extension_start(name, &EXTENSIONS).map_or(vec![], |i| name[i + 1..].split('.').map(|s| s.to_owned()).collect())
+    }
+
+    /// Returns the last extension of the file name, without dot prefix.
+    ///
+    /// The built-in compound extensions of [`CompoundExtensions::with_defaults`]
+    /// count as a single extension, as in [`.change_last_extension`];
+    /// [`.extension_with`] takes another registry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!(Some("y".into()), FlexPath::new_common("a/b.x.y").extension());
+    /// assert_eq!(Some("tar.gz".into()), FlexPath::new_common("a/b.tar.gz").extension());
+    /// assert_eq!(None, FlexPath::new_common("a.b/c").extension());
+    /// ```
+    pub fn extension(&self) -> Option<String> {
+        self.extension_with(&DEFAULT_COMPOUND_EXTENSIONS)
+    }
+
+    /// Returns the last extension of the file name, without dot prefix,
+    /// treating registered compound extensions as a single extension.
+    /// Compound extensions are compared as in [`ExtensionMatching::for_variant`].
+    pub fn extension_with(&self, compound: &CompoundExtensions) -> Option<String> {
+        let name = split_base_name(&self.0).1;
+        last_extension_start(name, compound, ExtensionMatching::for_variant(self.1)).map(|i| name[i + 1..].to_owned())
+    }
+
+    /// Returns the file name without its last extension.
+    ///
+    /// The built-in compound extensions of [`CompoundExtensions::with_defaults`]
+    /// count as a single extension, as in [`.change_last_extension`];
+    /// [`.file_stem_with`] takes another registry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!("b.x", FlexPath::new_common("a/b.x.y").file_stem());
+    /// assert_eq!("b", FlexPath::new_common("a/b.tar.gz").file_stem());
+    /// assert_eq!(".bashrc", FlexPath::new_common(".bashrc").file_stem());
+    /// ```
+    pub fn file_stem(&self) -> String {
+        self.file_stem_with(&DEFAULT_COMPOUND_EXTENSIONS)
+    }
+
+    /// Returns the file name without its last extension,
+    /// treating registered compound extensions as a single extension.
+    /// Compound extensions are compared as in [`ExtensionMatching::for_variant`].
+    pub fn file_stem_with(&self, compound: &CompoundExtensions) -> String {
+        let name = split_base_name(&self.0).1;
+        name[..last_extension_start(name, compound, ExtensionMatching::for_variant(self.1)).unwrap_or(name.len())].to_owned()
     }
 
     /// Checks if a file path has a specific extension.
     /// This method adds any lacking dot (`.`) prefix automatically to the
     /// `extension` argument.
+    ///
+    /// The file name must have more than leading dots before the extension,
+    /// so `.html` does not have the extension `.html`.
+    ///
+    /// Extensions are compared as in [`ExtensionMatching::for_variant`],
+    /// so they are compared case-insensitively for the `Windows` variant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexPath, FlexPathVariant};
+    /// assert!(FlexPath::new_common("a.tar.gz").has_extension("gz"));
+    /// assert!(FlexPath::new_common("a.tar.gz").has_extension("tar.gz"));
+    /// assert!(!FlexPath::new_common(".gz").has_extension("gz"));
+    /// assert!(!FlexPath::new_common("photo.JPG").has_extension("jpg"));
+    /// assert!(FlexPath::new("photo.JPG", FlexPathVariant::Windows).has_extension("jpg"));
+    /// ```
     pub fn has_extension(&self, extension: &str) -> bool {
-        has_extension(&self.0, extension)
+        has_extension(&self.0, extension, ExtensionMatching::for_variant(self.1))
+    }
+
+    /// Checks if a file path has a specific extension, comparing
+    /// extensions according to the given options.
+    pub fn has_extension_with(&self, extension: &str, matching: ExtensionMatching) -> bool {
+        has_extension(&self.0, extension, matching)
     }
 
     /// Checks if a file path has any of multiple specific extensions.
     /// This method adds any lacking dot (`.`) prefix automatically to each
     /// extension argument.
+    ///
+    /// Extensions are compared as in [`.has_extension`].
     pub fn has_extensions<'a, T: IntoIterator<Item = &'a str>>(&self, extensions: T) -> bool {
-        has_extensions(&self.0, extensions)
+        has_extensions(&self.0, extensions, ExtensionMatching::for_variant(self.1))
+    }
+
+    /// Checks if a file path has any of multiple specific extensions, comparing
+    /// extensions according to the given options.
+    pub fn has_extensions_with<'a, T: IntoIterator<Item = &'a str>>(&self, extensions: T, matching: ExtensionMatching) -> bool {
+        has_extensions(&self.0, extensions, matching)
     }
 
     /// Returns the base name of a file path.
@@ -224,16 +547,37 @@ impl FlexPath {
     /// This method adds any lacking dot (`.`) prefix automatically to each
     /// extension argument.
     ///
+    /// Extensions are matched as in [`.has_extension`]. If several
+    /// extensions match, the longest one is removed.
+    ///
     /// # Example
     /// 
     /// ```
     /// use file_paths::FlexPath;
     /// assert_eq!("qux", FlexPath::new_common("foo/qux.html").base_name_without_ext([".html"]));
+    /// assert_eq!("qux", FlexPath::new_common("foo/qux.tar.gz").base_name_without_ext(["gz", "tar.gz"]));
+    /// assert_eq!(".html", FlexPath::new_common("foo/.html").base_name_without_ext([".html"]));
     /// ```
     pub fn base_name_without_ext<'a, T>(&self, extensions: T) -> String
         where T: IntoIterator<Item = &'a str>
     {
-        base_name_without_ext(&self.0, extensions)
+        base_name_without_ext(&self.0, extensions, ExtensionMatching::for_variant(self.1))
+    }
+
+    /// Returns the base name of a file path, removing any of the specified extensions,
+    /// comparing extensions according to the given options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{ExtensionMatching, FlexPath};
+    /// let matching = ExtensionMatching::exact().case_insensitive(true);
+    /// assert_eq!("photo", FlexPath::new_common("a/photo.JPG").base_name_without_ext_with(["jpg"], matching));
+    /// ```
+    pub fn base_name_without_ext_with<'a, T>(&self, extensions: T, matching: ExtensionMatching) -> String
+        where T: IntoIterator<Item = &'a str>
+    {
+        base_name_without_ext(&self.0, extensions, matching)
     }
 
     /// Returns a string representation of the path,
@@ -248,35 +592,107 @@ impl FlexPath {
     }
 }
 
-impl ToString for FlexPath {
-    /// Returns a string representation of the path,
-    /// always delimiting segments with a forward slash (`/`).
-    fn to_string(&self) -> String {
-        self.0.clone()
+impl std::fmt::Display for FlexPath {
+    /// Formats the path, always delimiting segments
+    /// with a forward slash (`/`).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The result of [`FlexPath::relative_outcome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelativeOutcome {
+    /// The relative path from the base path to the target path.
+    Relative(String),
+    /// The resolved target path, given that it has a different
+    /// prefix from the base path.
+    Unrelated(FlexPath),
+}
+
+/// The kind of a symbolic link target. This is relevant for the `Windows` variant,
+/// where file and directory symbolic links are created differently.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum SymlinkKind {
+    /// The symbolic link points to a file.
+    File,
+    /// The symbolic link points to a directory.
+    Directory,
+}
+
+/// A symbolic link target computed by [`FlexPath::symlink_target_to`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkTarget {
+    text: String,
+    kind: SymlinkKind,
+}
+
+impl SymlinkTarget {
+    /// The text to store in the symbolic link.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The kind of the symbolic link.
+    pub fn kind(&self) -> SymlinkKind {
+        self.kind
     }
 }
 
+/// An error returned from [`FlexPath::strip_prefix`] if the
+/// base path is not an ancestor of the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StripPrefixError(());
+
+impl std::fmt::Display for StripPrefixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("prefix not found")
+    }
+}
+
+impl std::error::Error for StripPrefixError {}
+
 static STARTS_WITH_PATH_SEPARATOR: Lazy<Regex> = lazy_regex!(r"^[/\\]");
 
+static EXTENSIONS: Lazy<Regex> = lazy_regex!(r"(\.[^\.]+)+$");
+static LAST_EXTENSION: Lazy<Regex> = lazy_regex!(r"\.[^\.]+$");
+
+/// Splits a path into the part before the file name and the file name.
+fn split_base_name(path: &str) -> (&str, &str) {
+    path.split_at(path.rfind('/').map_or(0, |i| i + 1))
+}
+
+/// Returns the index where the extension matched by `regex` starts in a file name.
+/// Leading dots belong to the file name, so `.bashrc` has no extension.
+fn extension_start(name: &str, regex: &Regex) -> Option<usize> {
+    let body = name.trim_start_matches('.');
+    regex.find(body).map(|m| name.len() - body.len() + m.start())
+}
+
 fn change_extension(path: &str, extension: &str) -> String {
-    let extension = (if extension.starts_with('.') { "" } else { "." }).to_owned() + extension;
-    if regex_find!(r"(\.[^\.]+)+$", path).is_none() {
-        return path.to_owned() + &extension;
-    }
-    regex_replace!(r"(\.[^\.]+)+$", path, |_, _| &extension).into_owned()
+    let extension = extension_arg(extension);
+    let (dir, name) = split_base_name(path);
+    let name = &name[..extension_start(name, &EXTENSIONS).unwrap_or(name.len())];
+    dir.to_owned() + name + &extension
 }
 
-fn change_last_extension(path: &str, extension: &str) -> String {
-    let extension = (if extension.starts_with('.') { "" } else { "." }).to_owned() + extension;
+fn change_last_extension_with(path: &str, extension: &str, compound: &CompoundExtensions, matching: ExtensionMatching) -> String {
+    let extension = extension_arg(extension);
     assert!(
-        extension[1..].find('.').is_none(),
+        extension[1..].find('.').is_none() || compound.contains_matching(&extension, matching),
         "The argument to file_paths::change_last_extension() must only contain one extension; got {}",
         extension
     );
-    if regex_find!(r"(\..+)$", path).is_none() {
-        return path.to_owned() + &extension;
-    }
-    regex_replace!(r"(\..+)$", path, |_, _| &extension).into_owned()
+    let (dir, name) = split_base_name(path);
+    let name = &name[..last_extension_start(name, compound, matching).unwrap_or(name.len())];
+    dir.to_owned() + name + &extension
+}
+
+/// Returns the index where the last extension starts in a file name,
+/// preferring the longest registered compound extension.
+fn last_extension_start(name: &str, compound: &CompoundExtensions, matching: ExtensionMatching) -> Option<usize> {
+    compound.find(name, matching)
+        .or_else(|| extension_start(name, &LAST_EXTENSION))
 }
 
 /// Adds prefix dot to extension if missing.
@@ -284,28 +700,38 @@ fn extension_arg(extension: &str) -> String {
     (if extension.starts_with('.') { "" } else { "." }).to_owned() + extension
 }
 
-fn has_extension(path: &str, extension: &str) -> bool {
-    let extension = (if extension.starts_with('.') { "" } else { "." }).to_owned() + extension;
-    path.ends_with(&extension_arg(&extension))
+fn has_extension(path: &str, extension: &str, matching: ExtensionMatching) -> bool {
+    matching_extension_start(split_base_name(path).1, extension, matching).is_some()
+}
+
+/// Returns the index where `extension` starts in a file name if the file name
+/// has that extension. The file name must have more than leading dots before the extension.
+fn matching_extension_start(name: &str, extension: &str, matching: ExtensionMatching) -> Option<usize> {
+    let extension = extension_arg(extension);
+    // an extension starts with a dot, so only dots are candidate starts.
+    name.match_indices('.')
+        .map(|(i, _)| i)
+        .filter(|&i| !name[..i].trim_start_matches('.').is_empty())
+        .find(|&i| matching.eq(&name[i..], &extension))
 }
 
-fn has_extensions<'a, T: IntoIterator<Item = &'a str>>(path: &str, extensions: T) -> bool {
-    extensions.into_iter().any(|ext| has_extension(path, ext))
+fn has_extensions<'a, T: IntoIterator<Item = &'a str>>(path: &str, extensions: T, matching: ExtensionMatching) -> bool {
+    extensions.into_iter().any(|ext| has_extension(path, ext, matching))
 }
 
 fn base_name(path: &str) -> String {
-    path.split('/').last().map_or("", |s| s).to_owned()
+    split_base_name(path).1.to_owned()
 }
 
-fn base_name_without_ext<'a, T>(path: &str, extensions: T) -> String
+fn base_name_without_ext<'a, T>(path: &str, extensions: T, matching: ExtensionMatching) -> String
     where T: IntoIterator<Item = &'a str>
 {
-    let extensions = extensions.into_iter().map(extension_arg).collect::<Vec<String>>();
-    path.split('/').last().map_or("".to_owned(), |base| {
-        regex_replace!(r"(\.[^\.]+)+$", base, |_, prev_ext: &str| {
-            (if extensions.iter().any(|ext| ext == prev_ext) { "" } else { prev_ext }).to_owned()
-        }).into_owned()
-    })
+    let name = split_base_name(path).1;
+    let extension_start = extensions.into_iter()
+        .filter_map(|ext| matching_extension_start(name, ext, matching))
+        .min()
+        .unwrap_or(name.len());
+    name[..extension_start].to_owned()
 }
 
 #[cfg(test)]
@@ -323,6 +749,180 @@ mod test {
         assert_eq!("qux", FlexPath::new_common("foo/qux.html").base_name_without_ext([".html"]));
     }
 
+    #[test]
+    fn node_parse_and_format() {
+        let common = FlexPathVariant::Common;
+        let windows = FlexPathVariant::Windows;
+        // (variant, path, [root, dir, base, name, ext, format(parse(path))]), as recorded from Node.js
+        let vectors = [
+            (common, "", ["", "", "", "", "", ""]),
+            (common, "/", ["/", "/", "", "", "", "/"]),
+            (common, "//", ["/", "/", "", "", "", "/"]),
+            (common, "a", ["", "", "a", "a", "", "a"]),
+            (common, ".bashrc", ["", "", ".bashrc", ".bashrc", "", ".bashrc"]),
+            (common, "/home/user/.bashrc", ["/", "/home/user", ".bashrc", ".bashrc", "", "/home/user/.bashrc"]),
+            (common, "/home/user/dir/", ["/", "/home/user", "dir", "dir", "", "/home/user/dir"]),
+            (common, "file.tar.gz", ["", "", "file.tar.gz", "file.tar", ".gz", "file.tar.gz"]),
+            (common, "..", ["", "", "..", "..", "", ".."]),
+            (common, ".", ["", "", ".", ".", "", "."]),
+            (common, "...", ["", "", "...", "..", ".", "..."]),
+            (common, "a/..", ["", "a", "..", "..", "", "a/.."]),
+            (common, "a/b.", ["", "a", "b.", "b", ".", "a/b."]),
+            (common, "/a/b..c", ["/", "/a", "b..c", "b.", ".c", "/a/b..c"]),
+            (common, "//a//b.c//", ["/", "//a/", "b.c", "b", ".c", "//a//b.c"]),
+            (common, "C:foo", ["", "", "C:foo", "C:foo", "", "C:foo"]),
+            (common, "./a.b", ["", ".", "a.b", "a", ".b", "./a.b"]),
+            (windows, "", ["", "", "", "", "", ""]),
+            (windows, r"\", [r"\", r"\", "", "", "", r"\"]),
+            (windows, "C:", ["C:", "C:", "", "", "", "C:"]),
+            (windows, r"C:\", [r"C:\", r"C:\", "", "", "", r"C:\"]),
+            (windows, "C:foo", ["C:", "C:", "foo", "foo", "", "C:foo"]),
+            (windows, r"C:\foo\bar.txt", [r"C:\", r"C:\foo", "bar.txt", "bar", ".txt", r"C:\foo\bar.txt"]),
+            (windows, r"\\server\share\", [r"\\server\share\", r"\\server\share\", "", "", "", r"\\server\share\"]),
+            (windows, r"\\server\share", [r"\\server\share", r"\\server\share", "", "", "", r"\\server\share"]),
+            (windows, r"\\server\share\a.b", [r"\\server\share\", r"\\server\share\", "a.b", "a", ".b", r"\\server\share\a.b"]),
+            (windows, r"\\server", [r"\", r"\", "server", "server", "", r"\server"]),
+            (windows, "//server/share/x", ["//server/share/", "//server/share/", "x", "x", "", "//server/share/x"]),
+            (windows, ".bashrc", ["", "", ".bashrc", ".bashrc", "", ".bashrc"]),
+            (windows, r"a\..", ["", "a", "..", "..", "", r"a\.."]),
+            (windows, "c:/a/b.", ["c:/", "c:/a", "b.", "b", ".", r"c:/a\b."]),
+            (windows, "file.tar.gz", ["", "", "file.tar.gz", "file.tar", ".gz", "file.tar.gz"]),
+            (windows, r"\a\.b.c", [r"\", r"\a", ".b.c", ".b", ".c", r"\a\.b.c"]),
+
+        ];
+        for (variant, path, [root, dir, base, name, ext, formatted]) in vectors {
+            let parsed = ParsedPath::new(path, variant);
+            assert_eq!([root, dir, base, name, ext], [&parsed.root, &parsed.dir, &parsed.base, &parsed.name, &parsed.ext], "parse({path:?})");
+            assert_eq!(formatted, parsed.format(variant), "format(parse({path:?}))");
+        }
+
+        let parsed = ParsedPath { name: "a".into(), ext: "txt".into(), ..ParsedPath::default() };
+        assert_eq!("a.txt", parsed.format(common));
+        let parsed = ParsedPath { root: r"C:\".into(), name: "a".into(), ..ParsedPath::default() };
+        assert_eq!(r"C:\a", parsed.format(windows));
+        let parsed = ParsedPath { root: r"C:\".into(), dir: r"C:\x".into(), base: "a.b".into(), ..ParsedPath::default() };
+        assert_eq!("C:/x/a.b", FlexPath::format(&parsed, windows).to_string());
+
+        let parsed = FlexPath::new(r"\\server\share\a.b", windows).parse();
+        assert_eq!((r"\\server/share/", "a", ".b"), (parsed.root.as_str(), parsed.name.as_str(), parsed.ext.as_str()));
+    }
+
+    #[test]
+    fn pathlib_methods() {
+        let path = FlexPath::new_common("a/b.tar.gz");
+        assert_eq!("a/c", path.with_name("c").unwrap().to_string());
+        assert_eq!("a/c.gz", path.with_stem("c").unwrap().to_string());
+        assert_eq!("a/b.tar.zip", path.with_suffix(".zip").unwrap().to_string());
+        assert_eq!("a/b.tar", path.with_suffix("").unwrap().to_string());
+        assert_eq!("a/.bashrc.x", FlexPath::new_common("a/.bashrc").with_suffix(".x").unwrap().to_string());
+        assert_eq!("a/b..x", FlexPath::new_common("a/b.").with_suffix(".x").unwrap().to_string());
+        assert_eq!("c", FlexPath::new_common("b").with_stem("c").unwrap().to_string());
+        assert_eq!(Err(PathNameError::InvalidSuffix("zip".into())), path.with_suffix("zip"));
+        assert_eq!(Err(PathNameError::InvalidSuffix(".".into())), path.with_suffix("."));
+        assert_eq!(Err(PathNameError::InvalidSuffix("./x".into())), path.with_suffix("./x"));
+        assert_eq!(Err(PathNameError::InvalidName("b/c".into())), path.with_name("b/c"));
+        assert_eq!(Err(PathNameError::InvalidName("".into())), path.with_name(""));
+        assert_eq!(Err(PathNameError::NonEmptySuffix(path.clone())), path.with_stem(""));
+        assert_eq!(Err(PathNameError::EmptyName(FlexPath::new_common("/"))), FlexPath::new_common("/").with_name("c"));
+        assert!(FlexPath::new_common("").with_suffix(".x").is_err());
+
+        assert!(path.is_relative_to(&FlexPath::new_common("a")));
+        assert!(!path.is_relative_to(&FlexPath::new_common("b")));
+        assert_eq!("b.tar.gz", path.relative_to(&FlexPath::new_common("a")).unwrap().to_string());
+        assert_eq!("", path.relative_to(&path).unwrap().to_string());
+
+        let windows = FlexPathVariant::Windows;
+        assert!(FlexPath::new("C:/a", windows).with_name("d:x").is_err());
+        assert_eq!("C:/d", FlexPath::new("C:/a", windows).with_name("d").unwrap().to_string());
+        assert!(FlexPath::new("C:/", windows).with_name("x").is_err());
+        assert!(FlexPath::new(r"\\srv\share", windows).with_name("x").is_err());
+        assert_eq!(r"\\srv/share/x", FlexPath::new(r"\\srv\share\a", windows).with_name("x").unwrap().to_string());
+        assert!(FlexPath::new("C:/a", windows).with_name(r"b\c").is_err());
+        assert_eq!("B", FlexPath::new("C:/A/B", windows).relative_to(&FlexPath::new("c:/a", windows)).unwrap().to_string());
+        assert!(FlexPath::new("C:/A/B", windows).relative_to(&FlexPath::new("D:/a", windows)).is_err());
+    }
+
+    #[test]
+    fn compound_extensions() {
+        let compound = CompoundExtensions::with_defaults();
+        let path = FlexPath::new_common("src/lib.d.ts");
+        assert_eq!(vec!["d", "ts"], path.extensions());
+        assert_eq!(Some("d.ts".into()), path.extension());
+        assert_eq!(Some("d.ts".into()), path.extension_with(&compound));
+        assert_eq!(Some("ts".into()), path.extension_with(&CompoundExtensions::new()));
+        assert_eq!("lib", path.file_stem());
+        assert_eq!("lib.d", path.file_stem_with(&CompoundExtensions::new()));
+        assert_eq!("lib", path.file_stem_with(&compound));
+        assert_eq!("src/lib.js", path.change_last_extension("js").to_string());
+        assert_eq!("src/lib.d.js", path.change_last_extension_with("js", &CompoundExtensions::new()).to_string());
+        let archive = FlexPath::new_common("a.tar.gz");
+        assert_eq!((Some("tar.gz".into()), "a".into()), (archive.extension(), archive.file_stem()));
+        assert_eq!("a.zip", archive.change_last_extension("zip").to_string());
+        assert_eq!("src/lib.js", path.change_last_extension_with("js", &compound).to_string());
+        assert_eq!("a.b.js", FlexPath::new_common("a.b.ts").change_last_extension_with("js", &compound).to_string());
+        assert_eq!("a.tar.zst", FlexPath::new_common("a.tar.gz").change_last_extension_with("tar.zst", &compound).to_string());
+
+        let path = FlexPath::new_common(".d.ts");
+        assert_eq!(Some("ts".into()), path.extension_with(&compound));
+        assert_eq!(".d", path.file_stem_with(&compound));
+
+        let mut compound = CompoundExtensions::new();
+        assert_eq!(0, compound.iter().count());
+        assert_eq!(Some("gz".into()), FlexPath::new_common("a.tar.gz").extension_with(&compound));
+        compound.add(".tar.gz");
+        assert!(compound.contains("tar.gz"));
+        assert_eq!(Some("tar.gz".into()), FlexPath::new_common("a.tar.gz").extension_with(&compound));
+
+        // compound extensions follow the matching of the variant
+        let windows = FlexPathVariant::Windows;
+        assert_eq!(Some("TAR.GZ".into()), FlexPath::new(r"C:\x\A.TAR.GZ", windows).extension_with(&compound));
+        assert_eq!("A", FlexPath::new(r"C:\x\A.TAR.GZ", windows).file_stem_with(&compound));
+        assert_eq!("C:/x/A.zip", FlexPath::new(r"C:\x\A.TAR.GZ", windows).change_last_extension_with("zip", &compound).to_string());
+        assert_eq!(Some("GZ".into()), FlexPath::new_common("A.TAR.GZ").extension_with(&compound));
+    }
+
+    #[test]
+    #[should_panic]
+    fn compound_extension_argument_must_be_registered() {
+        FlexPath::new_common("a.ts").change_last_extension_with("x.ts", &CompoundExtensions::new());
+    }
+
+    #[test]
+    fn extensions_of_file_name() {
+        // (path, change_extension("x"), change_last_extension("x"))
+        let vectors = [
+            ("a", "a.x", "a.x"),
+            ("a.b", "a.x", "a.x"),
+            ("a.b.c", "a.x", "a.b.x"),
+            ("a.b/c", "a.b/c.x", "a.b/c.x"),
+            ("a.b/c.d.e", "a.b/c.x", "a.b/c.d.x"),
+            (".bashrc", ".bashrc.x", ".bashrc.x"),
+            (".bashrc.bak", ".bashrc.x", ".bashrc.x"),
+            ("..a.b", "..a.x", "..a.x"),
+            ("a/.config/b.c", "a/.config/b.x", "a/.config/b.x"),
+            ("a.", "a..x", "a..x"),
+            ("a..b", "a..x", "a..x"),
+        ];
+        for (path, change, change_last) in vectors {
+            assert_eq!(change, FlexPath::new_common(path).change_extension("x").to_string(), "change_extension({path:?})");
+            assert_eq!(change_last, FlexPath::new_common(path).change_last_extension("x").to_string(), "change_last_extension({path:?})");
+        }
+
+        assert!(FlexPath::new_common("a.b/c.d").has_extension("d"));
+        assert!(!FlexPath::new_common("a.b/c").has_extension("b"));
+        assert!(!FlexPath::new_common(".bashrc").has_extension("bashrc"));
+        assert!(FlexPath::new_common(".bashrc.bak").has_extension("bak"));
+        assert!(!FlexPath::new_common("..bak").has_extension("bak"));
+        assert!(FlexPath::new_common("a.tar.gz").has_extension(".tar.gz"));
+        assert!(!FlexPath::new_common("a.tgz").has_extension("gz"));
+
+        assert_eq!("a.tar", FlexPath::new_common("x.y/a.tar.gz").base_name_without_ext(["gz"]));
+        assert_eq!("a", FlexPath::new_common("x.y/a.tar.gz").base_name_without_ext(["gz", "tar.gz"]));
+        assert_eq!(".bashrc", FlexPath::new_common(".bashrc").base_name_without_ext(["bashrc"]));
+        assert_eq!(".bashrc", FlexPath::new_common(".bashrc.bak").base_name_without_ext(["bak"]));
+        assert_eq!("c", FlexPath::new_common("a.b/c").base_name_without_ext(["b"]));
+    }
+
     #[test]
     fn resolution() {
         assert_eq!("a", FlexPath::from_n_common(["a/b/.."]).to_string());
@@ -357,7 +957,847 @@ mod test {
         assert_eq!("", FlexPath::new("C:/", windows).relative("C:/"));
         assert_eq!("", FlexPath::new("C:/foo", windows).relative("C:/foo"));
         assert_eq!(r"\\foo", FlexPath::new("C:/", windows).relative(r"\\foo"));
-        assert_eq!("../../foo", FlexPath::new(r"\\a/b", windows).relative(r"\\foo"));
+        assert_eq!(r"\\foo", FlexPath::new(r"\\a/b", windows).relative(r"\\foo"));
+        assert_eq!("../d", FlexPath::new(r"\\a/b/c", windows).relative(r"\\A\B\d"));
+        assert_eq!("../ac", FlexPath::new("/ab", windows).relative("/ac"));
+        assert_eq!("b", FlexPath::new("c:/a", windows).relative("C:/a/b"));
+        assert_eq!("b", FlexPath::new("C:/A", windows).relative("C:/a/b"));
+        assert_eq!("../d", FlexPath::new(r"\\SRV\Share\A\c", windows).relative(r"\\srv\share\a\d"));
+        assert_eq!("../../c", FlexPath::new("A/b", windows).relative("a/../c"));
+        assert_eq!("b", FlexPath::new("A", windows).relative("a/b"));
+        assert_eq!("../a/b", FlexPath::new_common("/A").relative("/a/b"));
         assert_eq!("D:/", FlexPath::new("C:/", windows).relative(r"D:"));
+
+        assert_eq!("", FlexPath::new_common("").relative(""));
+        assert_eq!("a/b", FlexPath::new_common("").relative("a/b"));
+        assert_eq!("../..", FlexPath::new_common("a/b").relative(""));
+        assert_eq!("../../c/d", FlexPath::new_common("a/b").relative("c/d"));
+        assert_eq!("../c", FlexPath::new_common("a/b").relative("a/c"));
+        assert_eq!("../../x", FlexPath::new("a\\b", windows).relative("x"));
+        assert_eq!("../../b", FlexPath::new_common("a").relative("../b"));
+        assert_eq!("../../../b", FlexPath::new_common("a/c").relative("./../b"));
+        assert_eq!("../b", FlexPath::new_common("").relative("../b"));
+        assert_eq!("../../b", FlexPath::new("a", windows).relative(r"..\b"));
+        assert_eq!("../b", crate::common::relative("../a", "../b", |a, b| a == b));
+        assert_eq!("../b", FlexPath::new_common("/a").relative("/../b"));
+    }
+
+    #[test]
+    fn relative_outcomes() {
+        let windows = FlexPathVariant::Windows;
+        assert_eq!(RelativeOutcome::Relative("".into()), FlexPath::new("C:/", windows).relative_outcome("C:/"));
+        assert_eq!(RelativeOutcome::Relative("../c".into()), FlexPath::new_common("/a/b").relative_outcome("/a/c"));
+        assert_eq!(RelativeOutcome::Relative("../../b".into()), FlexPath::new_common("a").relative_outcome("../b"));
+        assert_eq!(RelativeOutcome::Unrelated(FlexPath::new("D:/", windows)), FlexPath::new("C:/", windows).relative_outcome("D:"));
+        assert_eq!(RelativeOutcome::Unrelated(FlexPath::new(r"\\srv\two\x", windows)), FlexPath::new(r"\\srv\one", windows).relative_outcome(r"\\srv\two\x"));
+        assert_eq!(RelativeOutcome::Unrelated(FlexPath::new("/x", windows)), FlexPath::new("C:/", windows).relative_outcome("/x"));
+    }
+
+    #[test]
+    fn symlink_targets() {
+        let link = FlexPath::new_common("/a/b/link");
+        assert_eq!("target", link.symlink_target_to(&FlexPath::new_common("/a/b/target"), false).text());
+        assert_eq!(".", link.symlink_target_to(&FlexPath::new_common("/a/b"), true).text());
+        assert_eq!("..", link.symlink_target_to(&FlexPath::new_common("/a"), true).text());
+        assert_eq!("../../x/y", link.symlink_target_to(&FlexPath::new_common("/x/y"), false).text());
+        assert_eq!("c", FlexPath::new_common("link").symlink_target_to(&FlexPath::new_common("c"), false).text());
+        assert_eq!("../c", FlexPath::new_common("a/link").symlink_target_to(&FlexPath::new_common("c"), false).text());
+        assert_eq!(SymlinkKind::File, link.symlink_target_to(&FlexPath::new_common("/x"), false).kind());
+
+        let windows = FlexPathVariant::Windows;
+        let link = FlexPath::new("C:/a/b/link", windows);
+        assert_eq!(r"..\..\x\y", link.symlink_target_to(&FlexPath::new("C:/x/y", windows), false).text());
+        assert_eq!(r"D:\x", link.symlink_target_to(&FlexPath::new("D:/x", windows), false).text());
+        let target = link.symlink_target_to(&FlexPath::new("C:/a/dir", windows), true);
+        assert_eq!((r"..\dir", SymlinkKind::Directory), (target.text(), target.kind()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn relativity_of_mixed_paths() {
+        FlexPath::new_common("/a").relative("b");
+    }
+
+    #[test]
+    #[should_panic]
+    fn relativity_above_the_shared_base() {
+        crate::common::relative("../../a", "../b", |a, b| a == b);
+    }
+
+    #[test]
+    fn prefixes() {
+        let base = FlexPath::new_common("/a/b");
+        assert!(FlexPath::new_common("/a/b").starts_with(&base));
+        assert!(FlexPath::new_common("/a/b/c").starts_with(&base));
+        assert!(!FlexPath::new_common("/a/bc").starts_with(&base));
+        assert!(!FlexPath::new_common("a/b/c").starts_with(&base));
+        assert!(FlexPath::new_common("a/b").starts_with(&FlexPath::new_common("")));
+        assert_eq!(Ok("".into()), FlexPath::new_common("/a/b").strip_prefix(&base));
+        assert_eq!(Ok("c/d".into()), FlexPath::new_common("/a/b/c/d").strip_prefix(&base));
+
+        let windows = FlexPathVariant::Windows;
+        assert_eq!(Ok("Foo".into()), FlexPath::new(r"c:\Work\Foo", windows).strip_prefix(&FlexPath::new("C:/work", windows)));
+        assert_eq!(Ok("x".into()), FlexPath::new(r"\\server\share\x", windows).strip_prefix(&FlexPath::new(r"\\server/share", windows)));
+        assert!(FlexPath::new("D:/work", windows).strip_prefix(&FlexPath::new("C:/work", windows)).is_err());
+    }
+
+    #[test]
+    fn mount_table() {
+        let windows = FlexPathVariant::Windows;
+        let mut mounts = MountTable::new();
+        mounts.add(FlexPath::new_common("/home/me/project"), FlexPath::new_common("/workspace"));
+        mounts.add(FlexPath::new_common("/home/me/project/target"), FlexPath::new_common("/cache/target"));
+        mounts.add(FlexPath::new("C:/Users/me/.cargo", windows), FlexPath::new_common("/usr/local/cargo"));
+
+        assert_eq!("/workspace/src/main.rs", mounts.to_container(&FlexPath::new_common("/home/me/project/src/main.rs")).unwrap().to_string());
+        assert_eq!("/cache/target/debug", mounts.to_container(&FlexPath::new_common("/home/me/project/target/debug")).unwrap().to_string());
+        assert_eq!("/home/me/project/target/x.d", mounts.to_host(&FlexPath::new_common("/cache/target/x.d")).unwrap().to_string());
+        assert_eq!("/home/me/project", mounts.to_host(&FlexPath::new_common("/workspace")).unwrap().to_string());
+        assert_eq!("C:/Users/me/.cargo/registry", mounts.to_host(&FlexPath::new_common("/usr/local/cargo/registry")).unwrap().to_string());
+
+        let unmapped = FlexPath::new_common("/home/me/projects");
+        assert_eq!(&unmapped, mounts.to_container(&unmapped).unwrap_err().path());
+    }
+
+    #[test]
+    fn rebasing() {
+        let path = FlexPath::new_common("/a/b/c");
+        assert_eq!("/x/c", path.rebase(&FlexPath::new_common("/a/b"), &FlexPath::new_common("/x")).unwrap().to_string());
+        assert_eq!("y/b/c", path.rebase(&FlexPath::new_common("/a"), &FlexPath::new_common("y")).unwrap().to_string());
+        assert_eq!("/x", path.rebase(&path, &FlexPath::new_common("/x")).unwrap().to_string());
+        assert!(path.rebase(&FlexPath::new_common("/a/bc"), &FlexPath::new_common("/x")).is_err());
+        assert!(path.rebase(&FlexPath::new_common("a"), &FlexPath::new_common("/x")).is_err());
+        assert_eq!("x/c", FlexPath::new_common("a/b/c").rebase(&FlexPath::new_common("a/b"), &FlexPath::new_common("x")).unwrap().to_string());
+
+        let windows = FlexPathVariant::Windows;
+        let path = FlexPath::new(r"C:\Users\me\file.txt", windows);
+        assert_eq!("D:/backup/me/file.txt", path.rebase(&FlexPath::new("c:/users", windows), &FlexPath::new("D:/backup", windows)).unwrap().to_string());
+        assert_eq!(r"\\srv/share/file.txt", path.rebase(&FlexPath::new("C:/Users/me", windows), &FlexPath::new(r"\\srv\share", windows)).unwrap().to_string());
+        assert!(path.rebase(&FlexPath::new("D:/Users", windows), &FlexPath::new("E:/", windows)).is_err());
+    }
+
+    #[test]
+    fn conformance_vectors() {
+        for line in include_str!("conformance_vectors.tsv").lines().filter(|line| !line.starts_with('#')) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let conformance = match fields[0] {
+                "node" => Conformance::Node,
+                "python" => Conformance::Python,
+                "go" => Conformance::Go,
+                other => panic!("unknown conformance {:?} in {:?}", other, line),
+            };
+            let variant = match fields[1] {
+                "common" => FlexPathVariant::Common,
+                "windows" => FlexPathVariant::Windows,
+                other => panic!("unknown variant {:?} in {:?}", other, line),
+            };
+            let actual = match fields[2] {
+                "normalize" => normalize_with(fields[3], variant, conformance),
+                "relative" => match relative_with(fields[3], fields[4], variant, conformance) {
+                    RelativeOutcome::Relative(path) => path,
+                    RelativeOutcome::Unrelated(_) => "!".into(),
+                },
+                other => panic!("unknown operation {:?} in {:?}", other, line),
+            };
+            assert_eq!(fields[5], actual, "{}", line);
+        }
+
+        // results that would depend on the working directory
+        let common = FlexPathVariant::Common;
+        for conformance in [Conformance::Node, Conformance::Python, Conformance::Go] {
+            assert_eq!(RelativeOutcome::Unrelated(FlexPath::new_common("b")), relative_with("../a", "b/", common, conformance));
+            assert!(matches!(FlexPath::new_common("/a").relative_with("b", conformance), RelativeOutcome::Unrelated(_)));
+        }
+
+        // normalized text is not a resolved `FlexPath`
+        assert_eq!("../x", normalize_with("../x", common, Conformance::Node));
+        assert_eq!(RelativeOutcome::Relative("../../b".into()), relative_with("../x/a", "../b", common, Conformance::Node));
+        for conformance in [Conformance::Node, Conformance::Python, Conformance::Go] {
+            assert_eq!(RelativeOutcome::Relative("..".into()), FlexPath::new_common("/a/b/").relative_with("/a", conformance));
+        }
+    }
+
+    #[test]
+    fn extension_matching() {
+        let windows = FlexPathVariant::Windows;
+        assert!(FlexPath::new("C:/photos/IMG_1.JPG", windows).has_extension(".jpg"));
+        assert!(FlexPath::new("a.Tar.GZ", windows).has_extensions(["zip", "tar.gz"]));
+        assert_eq!("IMG_1", FlexPath::new("C:/photos/IMG_1.JPG", windows).base_name_without_ext(["jpg"]));
+        assert!(!FlexPath::new_common("IMG_1.JPG").has_extension("jpg"));
+        assert!(!FlexPath::new(".JPG", windows).has_extension("jpg"));
+
+        // "caf\u{e9}" is precomposed and "cafe\u{301}" is decomposed
+        assert!(FlexPath::new_common("menu.caf\u{e9}").has_extension("cafe\u{301}"));
+        assert!(!FlexPath::new_common("menu.caf\u{e9}").has_extension_with("cafe\u{301}", ExtensionMatching::exact()));
+        assert_eq!("menu", FlexPath::new_common("menu.cafe\u{301}").base_name_without_ext(["caf\u{e9}"]));
+        assert!(FlexPath::new_common("menu.CAF\u{c9}").has_extension_with("cafe\u{301}", ExtensionMatching::exact().case_insensitive(true).unicode_normalized(true)));
+        assert!(!FlexPath::new("a.JPG", windows).has_extensions_with(["jpg"], ExtensionMatching::for_variant(windows).case_insensitive(false)));
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn mime_types() {
+        assert_eq!(Some("image/png"), FlexPath::new_common("a/b.PNG").mime_type());
+        assert_eq!(Some("application/gzip"), FlexPath::new_common("a/b.tar.gz").mime_type());
+        assert_eq!(Some("application/gzip"), FlexPath::new_common("a/b.gz").mime_type());
+        assert_eq!(Some("text/javascript"), FlexPath::new_common("app.min.js").mime_type());
+        assert_eq!(Some("application/json"), FlexPath::new_common("app.js.map").mime_type());
+        assert_eq!(Some("text/javascript"), FlexPath::new_common("a.b.js").mime_type());
+        assert_eq!(None, FlexPath::new_common(".js").mime_type());
+        assert_eq!(None, FlexPath::new_common("js").mime_type());
+        assert_eq!(FileCategory::Document, FlexPath::new_common("index.html").file_category());
+        assert_eq!(FileCategory::Video, FlexPath::new("C:/clips/a.MP4", FlexPathVariant::Windows).file_category());
+
+        let mut table = MimeTable::new();
+        table.add(".tar.gz", "application/x-compressed-tar", FileCategory::Archive);
+        assert_eq!(Some("application/x-compressed-tar"), FlexPath::new_common("a.TAR.GZ").mime_type_with(&table));
+        assert_eq!(None, FlexPath::new_common("a.gz").mime_type_with(&table));
+        assert_eq!(Some(("application/x-compressed-tar", FileCategory::Archive)), table.get("tar.gz"));
+        table.add("gz", "application/gzip", FileCategory::Archive);
+        assert_eq!(Some("application/gzip"), FlexPath::new_common("a.gz").mime_type_with(&table));
+    }
+
+    #[test]
+    fn globs() {
+        let windows = FlexPathVariant::Windows;
+        let glob = FlexGlob::new("src/**/*.{rs,toml}").unwrap();
+        assert!(glob.is_match(&FlexPath::new_common("src/main.rs")));
+        assert!(glob.is_match(&FlexPath::new_common("src/a/b/c.toml")));
+        assert!(glob.is_match(&FlexPath::new_common("./src/x/../main.rs")));
+        assert!(!glob.is_match(&FlexPath::new_common("src/main.rsx")));
+        assert!(!glob.is_match(&FlexPath::new_common("/src/main.rs")));
+        assert!(!glob.is_match(&FlexPath::new_common("src/Main.RS")));
+        assert!(glob.is_match(&FlexPath::new(r"src\Main.RS", windows)));
+
+        let glob = FlexGlob::new("**/*.rs").unwrap();
+        assert!(glob.is_match(&FlexPath::new_common("main.rs")));
+        assert!(glob.is_match(&FlexPath::new_common("/a/b/main.rs")));
+        assert!(glob.is_match(&FlexPath::new(r"C:\a\main.rs", windows)));
+
+        assert!(FlexGlob::new("/a/?.[ch]").unwrap().is_match(&FlexPath::new_common("/a/x.h")));
+        assert!(!FlexGlob::new("/a/?.[ch]").unwrap().is_match(&FlexPath::new_common("/a/xy.h")));
+        assert!(FlexGlob::new("[!a-c]*").unwrap().is_match(&FlexPath::new_common("dog")));
+        assert!(!FlexGlob::new("[^a-c]*").unwrap().is_match(&FlexPath::new_common("cat")));
+        assert!(FlexGlob::new("[]]").unwrap().is_match(&FlexPath::new_common("]")));
+        assert!(FlexGlob::new("[a-]").unwrap().is_match(&FlexPath::new_common("-")));
+        assert!(FlexGlob::new(r"\*.txt").unwrap().is_match(&FlexPath::new_common("*.txt")));
+        assert!(!FlexGlob::new(r"\*.txt").unwrap().is_match(&FlexPath::new_common("a.txt")));
+        assert!(FlexGlob::new(r"a\{b\}").unwrap().is_match(&FlexPath::new_common("a{b}")));
+        assert!(FlexGlob::new("{lib,src/{bin,tests}}/*.rs").unwrap().is_match(&FlexPath::new_common("src/tests/a.rs")));
+        assert!(FlexGlob::new("a/**/b").unwrap().is_match(&FlexPath::new_common("a/b")));
+        assert!(FlexGlob::new("a/**").unwrap().is_match(&FlexPath::new_common("a/b/c")));
+
+        assert!(FlexGlob::new("C:/Users/*/Desktop").unwrap().is_match(&FlexPath::new(r"c:\users\me\desktop", windows)));
+        assert!(!FlexGlob::new("C:/Users/*/Desktop").unwrap().is_match(&FlexPath::new(r"D:\Users\me\Desktop", windows)));
+        assert!(FlexGlob::new("//srv/share/**").unwrap().is_match(&FlexPath::new(r"\\SRV\share\x", windows)));
+
+        let glob = FlexGlob::new("!**/*.tmp").unwrap();
+        assert!(glob.is_negated());
+        assert!(glob.is_match(&FlexPath::new_common("a/b.rs")));
+        assert!(!glob.is_match(&FlexPath::new_common("a/b.tmp")));
+
+        assert_eq!(Some(vec!["a.b".to_owned(), "c".to_owned()]), FlexGlob::new("*.*").unwrap().captures(&FlexPath::new_common("a.b.c")));
+        assert_eq!(Some(vec!["".to_owned(), "x".to_owned()]), FlexGlob::new("a/**/?.rs").unwrap().captures(&FlexPath::new_common("a/x.rs")));
+
+        // matching takes polynomial time on patterns that make backtracking matchers exponential
+        let glob = FlexGlob::new("*a*a*a*a*a*a*a*a*a*a*a*ab").unwrap();
+        assert!(!glob.is_match(&FlexPath::new_common(&"a".repeat(1000))));
+        assert_eq!(None, glob.captures(&FlexPath::new_common(&"a".repeat(1000))));
+        let glob = FlexGlob::new("**/a/**/a/**/a/**/a/**/a/**/a/**/b").unwrap();
+        assert!(!glob.is_match(&FlexPath::new_common(&"a/".repeat(200))));
+        assert_eq!(7, glob.captures(&FlexPath::new_common(&("a/".repeat(200) + "b"))).unwrap().len());
+
+        assert_eq!(Err(GlobError::UnbalancedBraces("{a,b".into())), FlexGlob::new("{a,b"));
+        assert_eq!(Err(GlobError::UnbalancedBraces("a}".into())), FlexGlob::new("a}"));
+        assert_eq!(Err(GlobError::UnclosedClass("[ab".into())), FlexGlob::new("[ab"));
+        assert_eq!(Err(GlobError::TrailingEscape(r"a\".into())), FlexGlob::new(r"a\"));
+    }
+
+    #[test]
+    fn glob_sets() {
+        let windows = FlexPathVariant::Windows;
+        let mut set = FlexGlobSet::new();
+        assert!(set.is_empty());
+        for pattern in ["**/*.rs", "src/main.rs", "docs/**", "**/README.md", "*.tar.gz", "[ab]*", "!**/*.tmp", "C:/Users/*/Desktop", "**"] {
+            set.add(FlexGlob::new(pattern).unwrap());
+        }
+        assert_eq!(9, set.len());
+        assert_eq!("docs/**", set.globs()[2].pattern());
+
+        assert_eq!(vec![0, 1, 6, 8], set.matches(&FlexPath::new_common("src/main.rs")));
+        assert_eq!(vec![2, 3, 6, 8], set.matches(&FlexPath::new_common("docs/README.md")));
+        assert_eq!(vec![4, 5, 6, 8], set.matches(&FlexPath::new_common("a.tar.gz")));
+        assert_eq!(vec![8], set.matches(&FlexPath::new_common("x.tmp")));
+        assert_eq!(vec![0, 1, 6, 8], set.matches(&FlexPath::new(r"SRC\Main.RS", windows)));
+        assert_eq!(vec![6, 7, 8], set.matches(&FlexPath::new(r"c:\users\me\desktop", windows)));
+        assert_eq!(vec![8], set.matches(&FlexPath::new_common("/x.tmp")));
+        assert!(!FlexGlobSet::new().is_match(&FlexPath::new_common("x")));
+
+        // every pattern agrees with matching it on its own
+        let paths = ["src/main.rs", "/src/main.rs", "docs", "b/c.rs", "a/README.MD", "x.tar.gz", "src/a/b.tmp"];
+        for path in paths.map(FlexPath::new_common).into_iter().chain(paths.map(|p| FlexPath::new(p, windows))) {
+            let expected: Vec<usize> = set.globs().iter().enumerate().filter(|(_, g)| g.is_match(&path)).map(|(i, _)| i).collect();
+            assert_eq!(expected, set.matches(&path));
+        }
+    }
+
+    #[test]
+    fn brace_expansion() {
+        let expand = |pattern: &str, variant| -> Vec<String> {
+            expand_braces(pattern, variant).unwrap().iter().map(|p| p.to_string()).collect()
+        };
+        let common = FlexPathVariant::Common;
+        assert_eq!(vec!["out/debug/liba.so", "out/debug/libb.so", "out/debug/libc.so", "out/release/liba.so", "out/release/libb.so", "out/release/libc.so"], expand("out/{debug,release}/lib{a..c}.so", common));
+        assert_eq!(vec!["a/b", "a/c/d", "a/c/e", "a"], expand("a/{b,c/{d,e},}", common));
+        assert_eq!(vec!["f1", "f2", "f3"], expand("f{1..3}", common));
+        assert_eq!(vec!["f3", "f2", "f1"], expand("f{3..1}", common));
+        assert_eq!(vec!["f08", "f09", "f10"], expand("f{08..10}", common));
+        assert_eq!(vec!["f-1", "f0", "f1"], expand("f{-1..1}", common));
+        assert_eq!(vec!["0", "5", "10"], expand("{0..10..5}", common));
+        assert_eq!(vec!["e", "c", "a"], expand("{e..a..2}", common));
+        assert_eq!(vec!["a"], expand("{a}", common));
+        assert_eq!(vec!["x.."], expand("{x..}", common));
+        assert_eq!(vec!["{a,b}"], expand(r"\{a,b\}", common));
+        assert_eq!(vec!["C:/a/x", "C:/a/y"], expand(r"C:\a\{x,y}", FlexPathVariant::Windows));
+
+        assert_eq!(Err(BraceError::Unbalanced("a{b".into())), expand_braces("a{b", common));
+        assert_eq!(Err(BraceError::Unbalanced("a}b".into())), expand_braces("a}b", common));
+        assert_eq!(Err(BraceError::TooManyResults { pattern: "{1..1000}{1..1000}".into(), limit: 10_000 }), expand_braces("{1..1000}{1..1000}", common));
+        assert_eq!(Err(BraceError::TooManyResults { pattern: "{a,b,c}".into(), limit: 2 }), expand_braces_with_limit("{a,b,c}", common, 2));
+        assert!(expand_braces("{0..9223372036854775807}", common).is_err());
+
+        assert!(FlexGlob::new("log{1..3}.txt").unwrap().is_match(&FlexPath::new_common("log2.txt")));
+        assert_eq!(Err(GlobError::TooManyAlternatives("{1..100000}".into())), FlexGlob::new("{1..100000}"));
+    }
+
+    #[test]
+    fn ignore_files() {
+        let mut matcher = IgnoreMatcher::new();
+        matcher.add(FlexPath::new_common("/repo/app"), IgnoreFile::parse("!important.log\n/local.txt\nsrc/**/gen\n"));
+        matcher.add(FlexPath::new_common("/repo"), IgnoreFile::parse(concat!(
+            "# comment\n",
+            "\\#hash\n",
+            "\\!bang\n",
+            "*.log\n",
+            "/target\n",
+            "doc/*.html\n",
+            "build/\n",
+            "vendor/**\n",
+            "!vendor/keep\n",
+            "cache/\n",
+            "!cache/keep.txt\n",
+            "trailing\\ \n",
+            "spaces   \n",
+            "{a,b}\n",
+            "[unclosed\n",
+        )));
+        let ignored = |path: &str, is_dir: bool| matcher.is_ignored(&FlexPath::new_common(path), is_dir);
+
+        assert!(!ignored("/repo/# comment", false));
+        assert!(ignored("/repo/#hash", false));
+        assert!(ignored("/repo/!bang", false));
+        assert!(ignored("/repo/a/b/c.log", false));
+        assert!(!ignored("/repo/app/important.log", false));
+        assert!(ignored("/repo/important.log", false));
+
+        // anchored and floating patterns
+        assert!(ignored("/repo/target", false));
+        assert!(!ignored("/repo/a/target", false));
+        assert!(ignored("/repo/doc/index.html", false));
+        assert!(!ignored("/repo/a/doc/index.html", false));
+        assert!(!ignored("/repo/doc/a/index.html", false));
+        assert!(ignored("/repo/app/local.txt", false));
+        assert!(!ignored("/repo/local.txt", false));
+        assert!(ignored("/repo/app/src/gen", true));
+        assert!(ignored("/repo/app/src/a/b/gen", true));
+        assert!(!ignored("/repo/src/gen", true));
+
+        // directory-only patterns and ignored ancestors
+        assert!(ignored("/repo/build", true));
+        assert!(!ignored("/repo/build", false));
+        assert!(ignored("/repo/a/build/x.o", false));
+        assert!(ignored("/repo/cache/keep.txt", false));
+        assert!(!ignored("/repo/vendor", true));
+        assert!(ignored("/repo/vendor/lib.rs", false));
+        assert!(!ignored("/repo/vendor/keep", false));
+
+        // escapes, trailing spaces and literal braces
+        assert!(ignored("/repo/trailing ", false));
+        assert!(!ignored("/repo/trailing", false));
+        assert!(ignored("/repo/spaces", false));
+        assert!(ignored("/repo/{a,b}", false));
+        assert!(!ignored("/repo/a", false));
+
+        assert!(!ignored("/other/x.log", false));
+        assert!(!ignored("/repo", true));
+
+        let (dir, rule) = matcher.deciding_rule(&FlexPath::new_common("/repo/cache/keep.txt"), false).unwrap();
+        assert_eq!("/repo", dir.to_string());
+        assert_eq!("cache/", rule.pattern());
+        assert_eq!(10, rule.line());
+        assert!(rule.is_dir_only());
+        assert!(!rule.is_anchored());
+
+        let mut matcher = IgnoreMatcher::new();
+        matcher.add(FlexPath::new(r"C:\Repo", FlexPathVariant::Windows), IgnoreFile::parse("*.TMP\n"));
+        assert!(matcher.is_ignored(&FlexPath::new(r"c:\repo\a\b.tmp", FlexPathVariant::Windows), false));
+    }
+
+    #[test]
+    fn path_patterns() {
+        let pattern = PathPattern::new("assets/{locale}/{name}.{ext}").unwrap();
+        assert_eq!(vec!["locale", "name", "ext"], pattern.capture_names());
+        let captures = pattern.matches(&FlexPath::new_common("assets/pt-BR/app.tar.gz")).unwrap();
+        assert_eq!(vec![("ext", "gz"), ("locale", "pt-BR"), ("name", "app.tar")], captures.iter().collect::<Vec<_>>());
+        assert!(pattern.matches(&FlexPath::new_common("assets/en/.png")).is_none());
+        assert!(pattern.matches(&FlexPath::new_common("assets/en/a/b.png")).is_none());
+        assert!(pattern.matches(&FlexPath::new_common("/assets/en/b.png")).is_none());
+
+        let pattern = PathPattern::new("src/:module/**/*.rs").unwrap();
+        assert_eq!(2, pattern.wildcard_count());
+        let captures = pattern.matches(&FlexPath::new_common("src/net/main.rs")).unwrap();
+        assert_eq!(Some("net"), captures.get("module"));
+        assert_eq!(&["".to_owned(), "main".to_owned()], captures.wildcards());
+        let captures = pattern.matches(&FlexPath::new(r"SRC\net\a\b\Main.RS", FlexPathVariant::Windows)).unwrap();
+        assert_eq!(&["a/b".to_owned(), "Main".to_owned()], captures.wildcards());
+        assert!(pattern.matches(&FlexPath::new_common("SRC/net/main.rs")).is_none());
+
+        // a repeated name captures the same text
+        let pattern = PathPattern::new("{name}/{name}.rs").unwrap();
+        assert!(pattern.matches(&FlexPath::new_common("util/util.rs")).is_some());
+        assert!(pattern.matches(&FlexPath::new_common("util/main.rs")).is_none());
+
+        let pattern = PathPattern::new("*a*a*a*a*a*a*a*a*a*a*a*ab/**/a/**/a/**/{x}").unwrap();
+        assert!(pattern.matches(&FlexPath::new_common(&"a".repeat(1000))).is_none());
+        assert!(pattern.matches(&FlexPath::new_common(&("a".repeat(1000) + "b" + &"/a".repeat(200)))).is_some());
+
+        assert!(PathPattern::new(r"a/\{b\}/\:c").unwrap().matches(&FlexPath::new_common("a/{b}/:c")).is_some());
+        assert!(PathPattern::new("C:/Users/{user}").unwrap().matches(&FlexPath::new(r"c:\users\me", FlexPathVariant::Windows)).is_some());
+
+        let mut captures = PathCaptures::new();
+        captures.insert("locale", "en");
+        captures.push_wildcard("a/b");
+        let pattern = PathPattern::new("/out/{locale}/**/index.html").unwrap();
+        assert_eq!("/out/en/a/b/index.html", pattern.fill(&captures, FlexPathVariant::Common).unwrap().to_string());
+        assert_eq!(Err(PathPatternFillError::MissingCapture("name".into())), PathPattern::new("{name}").unwrap().fill(&captures, FlexPathVariant::Common));
+        assert_eq!(Err(PathPatternFillError::MissingWildcard(1)), PathPattern::new("**/*").unwrap().fill(&captures, FlexPathVariant::Common));
+        assert_eq!(Err(PathPatternFillError::InvalidValue("a/b".into())), PathPattern::new("*").unwrap().fill(&captures, FlexPathVariant::Common));
+
+        assert_eq!(Err(PathPatternError::UnclosedCapture("a/{b".into())), PathPattern::new("a/{b"));
+        assert_eq!(Err(PathPatternError::InvalidCaptureName("a b".into())), PathPattern::new("{a b}"));
+        assert_eq!(Err(PathPatternError::InvalidCaptureName("".into())), PathPattern::new("a/:"));
+        assert_eq!(Err(PathPatternError::TrailingEscape("a\\".into())), PathPattern::new("a\\"));
+    }
+
+    #[test]
+    fn path_rewriting() {
+        let mut rewriter = PathRewriter::new();
+        rewriter.add(RewriteRule::new("src/**/*.d.ts", "types/**/*.d.ts").unwrap());
+        rewriter.add(RewriteRule::new("src/**/*.ts", "dist/**/*.js").unwrap());
+        rewriter.add(RewriteRule::new("docs/{name}.md", "site/{name}/index").unwrap().with_extension(".html"));
+        assert_eq!(3, rewriter.rules().len());
+        assert_eq!(Some(".html"), rewriter.rules()[2].extension());
+
+        let rewrite = rewriter.rewrite(&FlexPath::new_common("src/a/b.d.ts")).unwrap().unwrap();
+        assert_eq!((0, "types/a/b.d.ts"), (rewrite.rule(), rewrite.path().to_string().as_str()));
+        let rewrite = rewriter.rewrite(&FlexPath::new_common("src/main.ts")).unwrap().unwrap();
+        assert_eq!((1, "dist/main.js"), (rewrite.rule(), rewrite.path().to_string().as_str()));
+        let rewrite = rewriter.rewrite(&FlexPath::new_common("docs/guide.md")).unwrap().unwrap();
+        assert_eq!((2, "site/guide/index.html"), (rewrite.rule(), rewrite.path().to_string().as_str()));
+        assert_eq!(Ok(None), rewriter.rewrite(&FlexPath::new_common("docs/a/guide.md")));
+        let rewrite = rewriter.rewrite(&FlexPath::new(r"SRC\App.TS", FlexPathVariant::Windows)).unwrap().unwrap();
+        assert_eq!(r"dist\App.js", rewrite.path().to_string_with_flex_separator());
+
+        let paths = [FlexPath::new_common("src/a.ts"), FlexPath::new_common("README.md"), FlexPath::new_common("src/a.ts")];
+        let rewrites = rewriter.rewrite_all(&paths).unwrap();
+        assert_eq!(vec![Some(1), None, Some(1)], rewrites.iter().map(|r| r.as_ref().map(|r| r.rule())).collect::<Vec<_>>());
+
+        let mut rewriter = PathRewriter::new();
+        rewriter.add(RewriteRule::new("{dir}/*.md", "site/*.html").unwrap());
+        rewriter.add(RewriteRule::new("pages/*.html", "site/*.html").unwrap());
+        let paths = [FlexPath::new_common("a/x.md"), FlexPath::new_common("pages/y.html"), FlexPath::new_common("pages/x.html")];
+        let Err(RewriteError::Collision(collision)) = rewriter.rewrite_all(&paths) else {
+            panic!("expected a collision");
+        };
+        assert_eq!("site/x.html", collision.output().to_string());
+        assert_eq!([(FlexPath::new_common("a/x.md"), 0), (FlexPath::new_common("pages/x.html"), 1)], *collision.sources());
+        let paths = [FlexPath::new("a/X.md", FlexPathVariant::Windows), FlexPath::new("b/x.MD", FlexPathVariant::Windows)];
+        assert!(matches!(rewriter.rewrite_all(&paths), Err(RewriteError::Collision(_))));
+
+        assert_eq!(Err(RewriteRuleError::UnknownCapture("name".into())), RewriteRule::new("{id}.md", "{name}.html"));
+        assert_eq!(Err(RewriteRuleError::UnmatchedWildcard(1)), RewriteRule::new("**/x", "**/*"));
+        assert_eq!(Err(RewriteRuleError::UnmatchedWildcard(0)), RewriteRule::new("**/x", "*"));
+        assert!(RewriteRule::new("*.md", "**/x").is_ok());
+        assert!(matches!(RewriteRule::new("{a", "b"), Err(RewriteRuleError::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn tree_mappings() {
+        let mut mapping = TreeMapping::new(FlexPath::new_common("/work/src"), FlexPath::new_common("/work/out"));
+        mapping.add_extension("scss", "css");
+        mapping.add_extension(".module.scss", ".module.css");
+        mapping.add_extension("less", "css");
+        mapping.add_extension("ts", "js");
+        mapping.add_extension("d.ts", "");
+
+        let to_dst = |path: &str| mapping.to_dst(&FlexPath::new_common(path)).unwrap().to_string();
+        let to_src = |path: &str| mapping.to_src(&FlexPath::new_common(path)).unwrap().to_string();
+        assert_eq!("/work/out/x/y.css", to_dst("/work/src/x/y.scss"));
+        assert_eq!("/work/out/x/y.module.css", to_dst("/work/src/x/y.module.scss"));
+        assert_eq!("/work/out/y.css", to_dst("/work/src/y.less"));
+        assert_eq!("/work/out/types/a", to_dst("/work/src/types/a.d.ts"));
+        assert_eq!("/work/out/README.md", to_dst("/work/src/README.md"));
+        assert_eq!("/work/out/.ts", to_dst("/work/src/.ts"));
+        assert_eq!("/work/out", to_dst("/work/src"));
+        assert_eq!("/work/src/x/y.scss", to_src("/work/out/x/y.css"));
+        assert_eq!("/work/src/a.ts", to_src("/work/out/a.js"));
+        assert!(mapping.to_dst(&FlexPath::new_common("/work/srcx/a.scss")).is_err());
+
+        let paths = [FlexPath::new_common("/work/src/a.ts"), FlexPath::new_common("/work/src/b/c.scss")];
+        let outputs = mapping.to_dst_all(&paths).unwrap();
+        assert_eq!(vec!["/work/out/a.js", "/work/out/b/c.css"], outputs.iter().map(|p| p.to_string()).collect::<Vec<_>>());
+        assert_eq!(paths.to_vec(), mapping.to_src_all(&outputs).unwrap());
+        let error = mapping.to_dst_all(&[paths[0].clone(), FlexPath::new_common("/tmp/a.ts")]).unwrap_err();
+        assert_eq!("/tmp/a.ts", error.path().to_string());
+
+        // Windows sources are matched case-insensitively, and outputs take the variant of `dst_root`
+        let mapping = TreeMapping {
+            src_root: FlexPath::new(r"C:\Project\Src", FlexPathVariant::Windows),
+            dst_root: FlexPath::new_common("dist"),
+            extension_map: vec![("TS".into(), "js".into())],
+        };
+        let output = mapping.to_dst(&FlexPath::new(r"c:\project\src\App.ts", FlexPathVariant::Windows)).unwrap();
+        assert_eq!((FlexPathVariant::Common, "dist/App.js"), (output.variant(), output.to_string().as_str()));
+        assert!(mapping.to_src(&FlexPath::new_common("dist/App.JS")).unwrap().to_string().ends_with("App.JS"));
+        assert_eq!(r"C:\Project\Src\App.TS", mapping.to_src(&output).unwrap().to_string_with_flex_separator());
+    }
+
+    #[test]
+    fn rename_plans() {
+        // applies the operations to a set of paths, checking that none overwrites a path
+        let apply = |paths: &[FlexPath], plan: &RenamePlan| -> Vec<String> {
+            let mut files: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+            for (from, to) in plan.operations() {
+                assert!(!files.contains(&to.to_string()), "{} overwrites {}", from, to);
+                let i = files.iter().position(|f| *f == from.to_string()).unwrap();
+                files[i] = to.to_string();
+            }
+            files.sort();
+            files
+        };
+        let paths = |list: &[&str]| list.iter().map(|p| FlexPath::new_common(p)).collect::<Vec<_>>();
+        let pairs = |plan: &RenamePlan| plan.renames().iter().map(|(a, b)| (a.to_string(), b.to_string())).collect::<Vec<_>>();
+
+        let files = paths(&["img/a.jpeg", "img/b.jpeg", "img/c.png"]);
+        let plan = plan_renames(&files, "img/*.jpeg", "{1}.jpg").unwrap();
+        assert_eq!(vec![("img/a.jpeg".to_owned(), "a.jpg".to_owned()), ("img/b.jpeg".into(), "b.jpg".into())], pairs(&plan));
+        assert_eq!(vec!["a.jpg", "b.jpg", "img/c.png"], apply(&files, &plan));
+        assert!(plan.cycles().is_empty());
+
+        // a chain runs from its end
+        let files = paths(&["f.old.old", "f.old"]);
+        let plan = plan_renames(&files, "*.old", "#1").unwrap();
+        assert_eq!(vec![("f.old.old".to_owned(), "f.old".to_owned()), ("f.old".into(), "f".into())], pairs(&plan));
+        assert_eq!(vec!["f", "f.old"], apply(&files, &plan));
+        assert_eq!("f.old", plan.operations()[0].0.to_string());
+
+        // swaps and cycles go through temporary names
+        let files = paths(&["x_y", "y_x", "x_y.tmp"]);
+        let plan = plan_renames(&files, "*_?", "#2_#1").unwrap();
+        assert_eq!(vec![vec![0, 1]], plan.cycles());
+        assert_eq!(vec!["x_y", "x_y.tmp", "y_x"], apply(&files, &plan));
+        assert_eq!("x_y.tmp1", plan.operations()[0].1.to_string());
+        assert_eq!(3, plan.operations().len());
+
+        let files = paths(&["d/a-b-c", "d/b-c-a", "d/c-a-b", "d/other"]);
+        let plan = plan_renames(&files, "d/*-*-*", "d/#2-#3-#1").unwrap();
+        assert_eq!(1, plan.cycles().len());
+        assert_eq!(3, plan.cycles()[0].len());
+        assert_eq!(4, plan.operations().len());
+        assert_eq!(vec!["d/a-b-c", "d/b-c-a", "d/c-a-b", "d/other"], apply(&files, &plan));
+
+        // collisions
+        let files = paths(&["a/x.txt", "b/x.txt"]);
+        assert_eq!(Err(RenameError::Collision { target: FlexPath::new_common("x.txt"), sources: [files[0].clone(), files[1].clone()] }),
+            plan_renames(&files, "*/*.txt", "#2.txt"));
+        let files = paths(&["a.txt", "a.md"]);
+        assert_eq!(Err(RenameError::TargetExists { source: files[0].clone(), target: files[1].clone() }),
+            plan_renames(&files, "*.txt", "#1.md"));
+        assert_eq!(Err(RenameError::MissingCapture { path: files[0].clone(), index: 2 }), plan_renames(&files, "*.txt", "#2"));
+        assert!(matches!(plan_renames(&files, "[a", "#1"), Err(RenameError::InvalidPattern(_))));
+
+        // a case-only rename of a `Windows` path does not collide with itself
+        let files = [FlexPath::new(r"C:\a.TXT", FlexPathVariant::Windows), FlexPath::new(r"C:\b.txt", FlexPathVariant::Windows)];
+        let plan = plan_renames(&files, "C:/*.TXT", "C:/#1.txt").unwrap();
+        assert_eq!(vec![("C:/a.TXT".to_owned(), "C:/a.txt".to_owned())], pairs(&plan));
+        assert!(matches!(plan_renames(&files, "C:/a.*", "C:/B.#1"), Err(RenameError::TargetExists { .. })));
+    }
+
+    #[test]
+    fn access_policies() {
+        let mut policy = AccessPolicy::new();
+        assert!(!policy.is_allowed(&FlexPath::new_common("/"), Access::Read));
+        policy.add(AccessRule::deny(&[Access::Read, Access::Write], AccessTarget::Glob(FlexGlob::new("**/.git/**").unwrap())));
+        policy.add(AccessRule::deny(&[Access::Write], AccessTarget::Subtree(FlexPath::new_common("/sandbox/plugin/config"))));
+        policy.add(AccessRule::allow(&[Access::Read, Access::Write], AccessTarget::Subtree(FlexPath::new_common("/sandbox/plugin"))));
+        policy.add(AccessRule::allow(&[Access::Read], AccessTarget::Glob(FlexGlob::new("/usr/{lib,share}/**").unwrap())));
+
+        let base = FlexPath::new_common("/sandbox/plugin");
+        let decide = |requested: &str, access| policy.decide(&base.resolve(requested), access);
+        assert_eq!(Some(2), decide("data/a.db", Access::Write).rule());
+        assert!(decide("data/a.db", Access::Write).is_allowed());
+        assert!(decide("config/settings.json", Access::Read).is_allowed());
+        assert_eq!(Some(1), decide("config/settings.json", Access::Write).rule());
+        assert!(!decide("config/settings.json", Access::Write).is_allowed());
+        assert_eq!(Some(0), decide("src/.git/HEAD", Access::Read).rule());
+        assert!(decide("/usr/share/dict/words", Access::Read).is_allowed());
+        assert!(!decide("/usr/share/dict/words", Access::Write).is_allowed());
+
+        // traversal attempts are decided on the resolved path
+        assert_eq!("/etc/passwd", flexible::resolve("/sandbox/plugin", "../../etc/passwd", FlexPathVariant::Common));
+        assert_eq!(None, decide("../../etc/passwd", Access::Read).rule());
+        assert!(!decide("../../../../../../etc/passwd", Access::Read).is_allowed());
+        assert!(!decide("data/../../plugin2/secret", Access::Read).is_allowed());
+        assert!(!decide("data/../config/./settings.json", Access::Write).is_allowed());
+        assert!(!decide("/sandbox/plugin/../plugin-other/x", Access::Read).is_allowed());
+        assert!(!decide("/sandbox/pluginx", Access::Read).is_allowed());
+        assert!(!decide("data/../.git/config", Access::Read).is_allowed());
+        assert!(!decide("/usr/share/../../etc/shadow", Access::Read).is_allowed());
+        assert!(!policy.is_allowed(&FlexPath::new_common("/sandbox/plugin/../../etc/passwd"), Access::Read));
+
+        let windows = FlexPathVariant::Windows;
+        let mut policy = AccessPolicy::new();
+        policy.add(AccessRule::allow(&[Access::Read], AccessTarget::Subtree(FlexPath::new(r"C:\Sandbox", windows))));
+        let base = FlexPath::new(r"C:\Sandbox\Plugin", windows);
+        assert!(policy.is_allowed(&base.resolve(r"..\..\sandbox\x"), Access::Read));
+        assert_eq!("C:/Windows/win.ini", flexible::resolve(r"C:\Sandbox\Plugin", r"..\..\Windows\win.ini", windows));
+        assert!(!policy.is_allowed(&base.resolve(r"..\..\Windows\win.ini"), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(r"D:\Sandbox\x"), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(r"\\server\share\Sandbox"), Access::Read));
+        assert_eq!("allow read subtree C:/Sandbox", policy.rules()[0].to_string());
+
+        // Win32 aliases of the same file
+        let mut policy = AccessPolicy::new();
+        policy.add(AccessRule::deny(&[Access::Read], AccessTarget::Glob(FlexGlob::new("**/.env").unwrap())));
+        policy.add(AccessRule::deny(&[Access::Read], AccessTarget::Subtree(FlexPath::new(r"C:\plugins\secret", windows))));
+        policy.add(AccessRule::allow(&[Access::Read], AccessTarget::Subtree(FlexPath::new(r"C:\plugins", windows))));
+        let base = FlexPath::new(r"C:\plugins", windows);
+        assert!(policy.is_allowed(&base.resolve("a.txt"), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(".env"), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(".env."), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(".env. . "), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(".env::$DATA"), Access::Read));
+        assert!(!policy.is_allowed(&base.resolve(".env:stream"), Access::Read));
+        assert_eq!(Some(1), policy.decide(&base.resolve(r"secret.\key"), Access::Read).rule());
+        assert_eq!(Some(1), policy.decide(&base.resolve(r"secret ::$INDEX_ALLOCATION\key"), Access::Read).rule());
+        assert_eq!(None, policy.decide(&base.resolve(r"x\.. \secret"), Access::Read).rule());
+        assert!(!policy.is_allowed(&base.resolve(r"x\.. \secret"), Access::Read));
+    }
+
+    #[test]
+    fn join_within_root() {
+        let root = FlexPath::new_common("/srv/uploads");
+        let join = |untrusted: &str| root.join_within(untrusted).map(|p| p.to_string());
+        assert_eq!(Ok("/srv/uploads/a/b.txt".into()), join("a/b.txt"));
+        assert_eq!(Ok("/srv/uploads/b.txt".into()), join(r"a\..\b.txt"));
+        assert_eq!(Ok("/srv/uploads".into()), join(""));
+        assert_eq!(Ok("/srv/uploads".into()), join("./a/.."));
+        assert_eq!(Ok("/srv/uploads/C:x".into()), join("C:x"));
+        assert_eq!(Ok("/srv/uploads/..x/x..".into()), join("..x/x.."));
+        assert_eq!(Err(EscapeError::LeavesRoot("..".into())), join(".."));
+        assert_eq!(Err(EscapeError::LeavesRoot("../../etc/passwd".into())), join("../../etc/passwd"));
+        assert_eq!(Err(EscapeError::LeavesRoot("a/../../uploads/x".into())), join("a/../../uploads/x"));
+        assert_eq!(Err(EscapeError::LeavesRoot(r"a\..\..\x".into())), join(r"a\..\..\x"));
+        assert_eq!(Err(EscapeError::Absolute("/etc/passwd".into())), join("/etc/passwd"));
+        assert_eq!(Err(EscapeError::Absolute(r"\etc".into())), join(r"\etc"));
+        assert_eq!(Err(EscapeError::Prefix("//host/x".into())), join("//host/x"));
+
+        let root = FlexPath::new_common("uploads");
+        assert_eq!(Ok("uploads/x".into()), root.join_within("x").map(|p| p.to_string()));
+        assert!(root.join_within("../x").is_err());
+
+        let root = FlexPath::new(r"C:\Data\Uploads", FlexPathVariant::Windows);
+        let join = |untrusted: &str| root.join_within(untrusted).map(|p| p.to_string_with_flex_separator());
+        assert_eq!(Ok(r"C:\Data\Uploads\a\b.txt".into()), join(r"a\b.txt"));
+        assert_eq!(Ok(r"C:\Data\Uploads\b.txt".into()), join("a/../b.txt"));
+        assert_eq!(Err(EscapeError::LeavesRoot(r"..\..\Windows\win.ini".into())), join(r"..\..\Windows\win.ini"));
+        assert_eq!(Err(EscapeError::Absolute(r"\Windows".into())), join(r"\Windows"));
+        assert_eq!(Err(EscapeError::Prefix(r"C:\Windows".into())), join(r"C:\Windows"));
+        assert_eq!(Err(EscapeError::Prefix("c:x".into())), join("c:x"));
+        assert_eq!(Err(EscapeError::Prefix(r"\\server\share\x".into())), join(r"\\server\share\x"));
+        assert_eq!(Err(EscapeError::Prefix(r"\\?\C:\x".into())), join(r"\\?\C:\x"));
+        assert_eq!(Err(EscapeError::Prefix(r"\\.\pipe\x".into())), join(r"\\.\pipe\x"));
+        assert_eq!(Err(EscapeError::Prefix(r"/\x".into())), join(r"/\x"));
+
+        let root = FlexPath::new(r"\\server\share\uploads", FlexPathVariant::Windows);
+        assert_eq!(r"\\server\share\uploads\x", root.join_within("x").unwrap().to_string_with_flex_separator());
+        assert!(root.join_within(r"..\..\..\x").is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn archive_paths() {
+        let entry = |name: &str| ArchivePath::new(name).map(|p| p.entry_name());
+        assert_eq!(Ok("a/b.txt".into()), entry("a/b.txt"));
+        assert_eq!(Ok("a/b.txt".into()), entry(r".\a\\b.txt"));
+        assert_eq!(Ok("b".into()), entry("a/../b"));
+        assert_eq!(Ok("dir/".into()), entry("dir/"));
+        assert_eq!(Ok("dir/".into()), entry(r"x\..\dir\"));
+        assert_eq!(Ok("..a/b..".into()), entry("..a/b.."));
+        assert_eq!(Ok("a/C:x".into()), entry("a/C:x"));
+        assert!(ArchivePath::new("dir/").unwrap().is_dir());
+        assert_eq!("dir", ArchivePath::new("dir/").unwrap().as_str());
+        assert_eq!("dir/", ArchivePath::new("dir/").unwrap().to_string());
+
+        assert_eq!(Err(ArchivePathError::LeavesRoot("..".into())), entry(".."));
+        assert_eq!(Err(ArchivePathError::LeavesRoot("a/../../b".into())), entry("a/../../b"));
+        assert_eq!(Err(ArchivePathError::LeavesRoot(r"a\..\..\b".into())), entry(r"a\..\..\b"));
+        assert_eq!(Err(ArchivePathError::Absolute("/etc/passwd".into())), entry("/etc/passwd"));
+        assert_eq!(Err(ArchivePathError::Absolute(r"\etc".into())), entry(r"\etc"));
+        assert_eq!(Err(ArchivePathError::Prefix("c:evil".into())), entry("c:evil"));
+        assert_eq!(Err(ArchivePathError::Prefix(r"\\host\share\x".into())), entry(r"\\host\share\x"));
+        assert_eq!(Err(ArchivePathError::Prefix("//host/x".into())), entry("//host/x"));
+        assert_eq!(Err(ArchivePathError::Prefix("./C:x".into())), entry("./C:x"));
+        assert_eq!(Err(ArchivePathError::Prefix("a/../C:/x".into())), entry("a/../C:/x"));
+        assert_eq!(Err(ArchivePathError::Prefix(r".\\c:\x".into())), entry(r".\\c:\x"));
+        assert_eq!(Err(ArchivePathError::Empty("./".into())), entry("./"));
+        assert_eq!(Err(ArchivePathError::Empty("".into())), entry(""));
+        assert_eq!(Err(ArchivePathError::InvalidCharacter("a\0b".into())), entry("a\0b"));
+
+        let entry = ArchivePath::new("docs/a.md").unwrap();
+        assert_eq!("/out/docs/a.md", entry.extract_to(&FlexPath::new_common("/out")).unwrap().to_string());
+        assert_eq!(r"C:\out\docs\a.md", entry.extract_to(&FlexPath::new(r"C:\out", FlexPathVariant::Windows)).unwrap().to_string_with_flex_separator());
+        let windows_root = FlexPath::new(r"C:\out", FlexPathVariant::Windows);
+        assert_eq!(Err(ArchivePathError::Colon("a/C:x".into())), ArchivePath::new("a/C:x").unwrap().extract_to(&windows_root));
+        assert_eq!(Err(ArchivePathError::Colon("a/c:/x".into())), ArchivePath::new(r"a/c:\x").unwrap().extract_to(&windows_root));
+        assert_eq!(Err(ArchivePathError::Colon("a.txt:stream".into())), ArchivePath::new("a.txt:stream").unwrap().extract_to(&windows_root));
+        assert_eq!("/out/a/C:x", ArchivePath::new("a/C:x").unwrap().extract_to(&FlexPath::new_common("/out")).unwrap().to_string());
+
+        // ustar fields
+        let dir = "d".repeat(150);
+        let (prefix, name) = ArchivePath::new(&format!("{dir}/{}", "n".repeat(100))).unwrap().ustar_fields().unwrap();
+        assert_eq!((150, 100), (prefix.len(), name.len()));
+        let (prefix, name) = ArchivePath::new(&format!("{}/{}/{}/f", "a".repeat(60), "b".repeat(60), "c".repeat(60))).unwrap().ustar_fields().unwrap();
+        assert_eq!((format!("{}/{}", "a".repeat(60), "b".repeat(60)), format!("{}/f", "c".repeat(60))), (prefix, name));
+        let (prefix, name) = ArchivePath::new(&format!("{}/{}/", "a".repeat(155), "b".repeat(10))).unwrap().ustar_fields().unwrap();
+        assert_eq!(("a".repeat(155), "b".repeat(10) + "/"), (prefix, name));
+        assert_eq!(None, ArchivePath::new(&format!("{}/f", "a".repeat(156))).unwrap().ustar_fields());
+        assert_eq!(None, ArchivePath::new(&format!("a/{}", "f".repeat(101))).unwrap().ustar_fields());
+    }
+
+    #[test]
+    fn object_keys() {
+        let keys: Vec<ObjectKey> = ["a/b", "a/./b", "a/../b", "a//b", "a/b/c", "a/b/d/e", "a/", "ab", "b", "a/b.txt"]
+            .iter().map(|k| ObjectKey::new(k)).collect();
+        let set: std::collections::HashSet<&ObjectKey> = keys.iter().collect();
+        assert_eq!(keys.len(), set.len());
+        assert_eq!(vec!["a", "", "b"], keys[3].segments().collect::<Vec<_>>());
+        assert_eq!("", keys[6].name());
+        assert!(keys[7].starts_with("a"));
+        assert_eq!("a/b", ObjectKey::new("a/").join("b").as_str());
+        assert_eq!("b", ObjectKey::new("").join("b").as_str());
+        assert_eq!("a/../b", ObjectKey::new("a").join("../b").as_str());
+
+        let names = |listing: &ObjectListing| listing.keys().iter().map(|k| k.as_str().to_owned()).collect::<Vec<_>>();
+        let listing = list_keys(&keys, "", Some("/"));
+        assert_eq!(vec!["ab", "b"], names(&listing));
+        assert_eq!(&["a/".to_owned()], listing.common_prefixes());
+        let listing = list_keys(&keys, "a/", Some("/"));
+        assert_eq!(vec!["a/", "a/b", "a/b.txt"], names(&listing));
+        assert_eq!(&["a/../".to_owned(), "a/./".to_owned(), "a//".to_owned(), "a/b/".to_owned()], listing.common_prefixes());
+        let listing = list_keys(&keys, "a/b", None);
+        assert_eq!(vec!["a/b", "a/b.txt", "a/b/c", "a/b/d/e"], names(&listing));
+        assert!(listing.common_prefixes().is_empty());
+        let listing = list_keys(&keys, "a/b", Some("."));
+        assert_eq!(vec!["a/b", "a/b/c", "a/b/d/e"], names(&listing));
+        assert_eq!(&["a/b.".to_owned()], listing.common_prefixes());
+        let listing = list_keys(&keys, "a/b/", Some("/"));
+        assert_eq!(vec!["a/b/c"], names(&listing));
+        assert_eq!(&["a/b/d/".to_owned()], listing.common_prefixes());
+        assert_eq!(ObjectListing::default(), list_keys(&keys, "z", Some("/")));
+
+        let url = ObjectUrl::parse("gs://data.example.com/reports//2024/./q1.csv").unwrap();
+        assert_eq!((ObjectStore::Gcs, "data.example.com", "reports//2024/./q1.csv"), (url.store(), url.bucket(), url.key().as_str()));
+        assert_eq!("gs://data.example.com/reports//2024/./q1.csv", url.to_string());
+        let url = ObjectUrl::parse("S3://bucket").unwrap();
+        assert_eq!((ObjectStore::S3, ""), (url.store(), url.key().as_str()));
+        assert_eq!("s3://bucket/", url.to_string());
+        assert_eq!("", ObjectUrl::parse("s3://bucket/").unwrap().key().as_str());
+        assert_eq!("/x", ObjectUrl::parse("s3://bucket//x").unwrap().key().as_str());
+        assert_eq!(ObjectUrl::new(ObjectStore::S3, "bucket", ObjectKey::new("k")), ObjectUrl::parse("s3://bucket/k"));
+        assert_eq!(Err(ObjectUrlError::InvalidScheme("https://bucket/k".into())), ObjectUrl::parse("https://bucket/k"));
+        assert_eq!(Err(ObjectUrlError::InvalidScheme("bucket/k".into())), ObjectUrl::parse("bucket/k"));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket("My_Bucket".into())), ObjectUrl::parse("s3://My_Bucket/k"));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket("ab".into())), ObjectUrl::parse("s3://ab/k"));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket("".into())), ObjectUrl::parse("s3:///k"));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket("-bucket".into())), ObjectUrl::parse("gs://-bucket"));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket("my_bucket".into())), ObjectUrl::parse("s3://my_bucket/k"));
+        assert!(ObjectUrl::parse("gs://my_bucket/k").is_ok());
+        let long = "b".repeat(64);
+        assert_eq!(Err(ObjectUrlError::InvalidBucket(long.clone())), ObjectUrl::parse(&format!("s3://{long}")));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket(long.clone())), ObjectUrl::parse(&format!("gs://{long}")));
+        assert!(ObjectUrl::parse(&format!("s3://{}", "b".repeat(63))).is_ok());
+        let dotted = ["c".repeat(63), "d".repeat(63), "e".repeat(63), "f".repeat(30)].join(".");
+        assert_eq!(222, dotted.len());
+        assert!(ObjectUrl::parse(&format!("gs://{dotted}")).is_ok());
+        assert_eq!(Err(ObjectUrlError::InvalidBucket(dotted.clone())), ObjectUrl::parse(&format!("s3://{dotted}")));
+        let dotted = format!("{}.{long}", "c".repeat(10));
+        assert_eq!(Err(ObjectUrlError::InvalidBucket(dotted.clone())), ObjectUrl::parse(&format!("gs://{dotted}")));
+    }
+
+    #[test]
+    fn url_references() {
+        // RFC 3986, section 5.4
+        let base = UrlReference::parse("http://a/b/c/d;p?q");
+        let cases = [
+            ("g:h", "g:h"), ("g", "http://a/b/c/g"), ("./g", "http://a/b/c/g"), ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"), ("//g", "http://g"), ("?y", "http://a/b/c/d;p?y"), ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"), ("g#s", "http://a/b/c/g#s"), ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"), ("g;x", "http://a/b/c/g;x"), ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"), ("./", "http://a/b/c/"), ("..", "http://a/b/"), ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"), ("../..", "http://a/"), ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"), ("../../../../g", "http://a/g"), ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"), ("g.", "http://a/b/c/g."), (".g", "http://a/b/c/.g"), ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"), ("./../g", "http://a/b/g"), ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"), ("g/../h", "http://a/b/c/h"), ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+            ("g;x=1/../y", "http://a/b/c/y"), ("g?y/./x", "http://a/b/c/g?y/./x"), ("g?y/../x", "http://a/b/c/g?y/../x"),
+            ("g#s/./x", "http://a/b/c/g#s/./x"), ("g#s/../x", "http://a/b/c/g#s/../x"), ("http:g", "http:g"),
+        ];
+        for (reference, expected) in cases {
+            assert_eq!(expected, base.resolve(&UrlReference::parse(reference)).to_string(), "{}", reference);
+        }
+
+        let url = UrlReference::parse("https://user@host:8080/p/a?q=1#f");
+        assert_eq!((Some("https"), Some("user@host:8080"), "/p/a", Some("q=1"), Some("f")),
+            (url.scheme(), url.authority(), url.path(), url.query(), url.fragment()));
+        let empty = UrlReference::parse("?#");
+        assert_eq!((None, None, "", Some(""), Some("")), (empty.scheme(), empty.authority(), empty.path(), empty.query(), empty.fragment()));
+
+        // relative links resolve back to their targets
+        let bases = ["/a/b/c", "/a/b/", "/a/b/c?q", "/", "/a//b/x", "http://h/a/b", "http://h"];
+        let targets = ["/a/b/c", "/a/b/c?q", "/a/b/c#f", "/a/b/c?r#f", "/a/b/", "/a/d/e?x#y", "/", "/x:y/z", "/a/b//z",
+            "/a//b/c", "/a/%62/c", "http://h/a/b?q", "http://other/a", "mailto:x@y"];
+        for base in bases.map(UrlReference::parse) {
+            for target in targets.map(UrlReference::parse) {
+                let link = base.relative(&target);
+                assert!(base.resolve(&link).equivalent(&base.resolve(&target)), "{} -> {} via {}", base, target, link);
+            }
+        }
+        let page = UrlReference::parse("/a/b/c?q");
+        assert_eq!("c", page.relative(&UrlReference::parse("/a/b/c")).to_string());
+        assert_eq!("./x:y", UrlReference::parse("/a").relative(&UrlReference::parse("/x:y")).to_string());
+        assert_eq!("http://other/a", UrlReference::parse("http://h/a").relative(&UrlReference::parse("http://other/a")).to_string());
+        assert_eq!("../c/d", UrlReference::parse("/%61/b/x").relative(&UrlReference::parse("/a/c/d")).to_string());
+
+        assert!(UrlReference::parse("/caf%c3%a9").equivalent(&UrlReference::parse("/café")));
+        assert!(!UrlReference::parse("/a?q").equivalent(&UrlReference::parse("/a")));
+        assert_eq!("/a//c/", remove_dot_segments("/a//b/../c/."));
+
+        // non-ASCII first segments
+        assert_eq!("é/x", remove_dot_segments("é/x"));
+        assert_eq!("ü/g", remove_dot_segments("ü/./g"));
+        assert_eq!("é", UrlReference::parse("a").resolve(&UrlReference::parse("é")).to_string());
+        assert_eq!("/a/ü/é", UrlReference::parse("/a/b").resolve(&UrlReference::parse("ü/é")).to_string());
+        assert_eq!("ü", UrlReference::parse("/a/b").relative(&UrlReference::parse("ü")).to_string());
+        assert_eq!("../ü/é", UrlReference::parse("/a/b/c").relative(&UrlReference::parse("/a/ü/é")).to_string());
+    }
+}
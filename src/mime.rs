@@ -0,0 +1,214 @@
+/*!
+MIME type and file category lookup from extensions.
+This module is available with the `mime` feature.
+*/
+
+use super::{extension_arg, split_base_name, FlexPath};
+use lazy_regex::Lazy;
+use std::collections::HashMap;
+
+/// A coarse category of files, as determined by their extension.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum FileCategory {
+    /// Images, such as `.png` and `.svg`.
+    Image,
+    /// Videos, such as `.mp4`.
+    Video,
+    /// Audio, such as `.mp3`.
+    Audio,
+    /// Source code and configuration, such as `.rs`, `.js` and `.json`.
+    SourceCode,
+    /// Archives and compressed files, such as `.zip` and `.tar.gz`.
+    Archive,
+    /// Documents, such as `.html`, `.md` and `.pdf`.
+    Document,
+    /// Any other or unknown file.
+    Other,
+}
+
+/// A table mapping extensions to MIME types and file categories.
+///
+/// Extensions are compared case-insensitively, and the longest matching
+/// extension wins, so compound extensions such as `.tar.gz` and `.d.ts`
+/// take precedence over `.gz` and `.ts`.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FileCategory, FlexPath, MimeTable};
+///
+/// let mut table = MimeTable::with_defaults();
+/// table.add("glb", "model/gltf-binary", FileCategory::Other);
+/// assert_eq!(Some("model/gltf-binary"), FlexPath::new_common("scene.GLB").mime_type_with(&table));
+/// assert_eq!(Some("application/gzip"), FlexPath::new_common("a.tar.gz").mime_type_with(&table));
+/// assert_eq!(FileCategory::Archive, FlexPath::new_common("a.tar.gz").file_category_with(&table));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MimeTable {
+    entries: HashMap<String, (String, FileCategory)>,
+}
+
+impl MimeTable {
+    /// Constructs an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a table containing built-in MIME types of common
+    /// image, video, audio, source code, archive, document and font extensions.
+    pub fn with_defaults() -> Self {
+        use FileCategory::*;
+        let mut r = Self::new();
+        for (extensions, mime_type, category) in [
+            (&["png"][..], "image/png", Image),
+            (&["jpg", "jpeg"], "image/jpeg", Image),
+            (&["gif"], "image/gif", Image),
+            (&["webp"], "image/webp", Image),
+            (&["avif"], "image/avif", Image),
+            (&["svg"], "image/svg+xml", Image),
+            (&["ico"], "image/vnd.microsoft.icon", Image),
+            (&["bmp"], "image/bmp", Image),
+            (&["tif", "tiff"], "image/tiff", Image),
+
+            (&["mp4", "m4v"], "video/mp4", Video),
+            (&["webm"], "video/webm", Video),
+            (&["mkv"], "video/x-matroska", Video),
+            (&["mov"], "video/quicktime", Video),
+            (&["avi"], "video/x-msvideo", Video),
+            (&["mpeg", "mpg"], "video/mpeg", Video),
+
+            (&["mp3"], "audio/mpeg", Audio),
+            (&["wav"], "audio/wav", Audio),
+            (&["ogg", "oga"], "audio/ogg", Audio),
+            (&["opus"], "audio/opus", Audio),
+            (&["flac"], "audio/flac", Audio),
+            (&["aac"], "audio/aac", Audio),
+            (&["m4a"], "audio/mp4", Audio),
+
+            (&["js", "mjs", "cjs", "min.js", "min.mjs"], "text/javascript", SourceCode),
+            (&["ts", "mts", "cts", "tsx", "d.ts", "d.mts", "d.cts"], "text/typescript", SourceCode),
+            (&["jsx"], "text/jsx", SourceCode),
+            (&["css", "min.css"], "text/css", SourceCode),
+            (&["json", "js.map", "css.map"], "application/json", SourceCode),
+            (&["xml"], "application/xml", SourceCode),
+            (&["toml"], "application/toml", SourceCode),
+            (&["yaml", "yml"], "application/yaml", SourceCode),
+            (&["wasm"], "application/wasm", SourceCode),
+            (&["rs"], "text/x-rust", SourceCode),
+            (&["py"], "text/x-python", SourceCode),
+            (&["c", "h"], "text/x-c", SourceCode),
+            (&["cc", "cpp", "cxx", "hpp"], "text/x-c++", SourceCode),
+            (&["java"], "text/x-java", SourceCode),
+            (&["go"], "text/x-go", SourceCode),
+            (&["sh"], "application/x-sh", SourceCode),
+
+            (&["zip"], "application/zip", Archive),
+            (&["tar"], "application/x-tar", Archive),
+            (&["gz", "tgz", "tar.gz"], "application/gzip", Archive),
+            (&["bz2", "tar.bz2"], "application/x-bzip2", Archive),
+            (&["xz", "tar.xz"], "application/x-xz", Archive),
+            (&["zst", "tar.zst"], "application/zstd", Archive),
+            (&["7z"], "application/x-7z-compressed", Archive),
+            (&["rar"], "application/vnd.rar", Archive),
+
+            (&["html", "htm"], "text/html", Document),
+            (&["txt"], "text/plain", Document),
+            (&["md", "markdown"], "text/markdown", Document),
+            (&["csv"], "text/csv", Document),
+            (&["pdf"], "application/pdf", Document),
+            (&["rtf"], "application/rtf", Document),
+            (&["epub"], "application/epub+zip", Document),
+            (&["doc"], "application/msword", Document),
+            (&["docx"], "application/vnd.openxmlformats-officedocument.wordprocessingml.document", Document),
+            (&["xls"], "application/vnd.ms-excel", Document),
+            (&["xlsx"], "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", Document),
+            (&["ppt"], "application/vnd.ms-powerpoint", Document),
+            (&["pptx"], "application/vnd.openxmlformats-officedocument.presentationml.presentation", Document),
+            (&["odt"], "application/vnd.oasis.opendocument.text", Document),
+
+            (&["woff"], "font/woff", Other),
+            (&["woff2"], "font/woff2", Other),
+            (&["ttf"], "font/ttf", Other),
+            (&["otf"], "font/otf", Other),
+        ] {
+            for extension in extensions {
+                r.add(extension, mime_type, category);
+            }
+        }
+        r
+    }
+
+    /// Maps an extension, which may be a compound extension such as `tar.gz`,
+    /// to a MIME type and a category, replacing any previous mapping.
+    /// This method adds any lacking dot (`.`) prefix automatically to the
+    /// `extension` argument.
+    pub fn add(&mut self, extension: &str, mime_type: &str, category: FileCategory) {
+        self.entries.insert(extension_arg(extension).to_lowercase(), (mime_type.to_owned(), category));
+    }
+
+    /// Returns the MIME type and category mapped to an extension.
+    /// This method adds any lacking dot (`.`) prefix automatically to the
+    /// `extension` argument.
+    pub fn get(&self, extension: &str) -> Option<(&str, FileCategory)> {
+        self.entries.get(&extension_arg(extension).to_lowercase()).map(|(mime_type, category)| (mime_type.as_str(), *category))
+    }
+
+    /// Looks up the longest mapped extension of a file name. As in
+    /// [`FlexPath::has_extension`], leading dots belong to the file name.
+    fn lookup(&self, name: &str) -> Option<(&str, FileCategory)> {
+        let name = name.to_lowercase();
+        // the leftmost matching dot gives the longest extension.
+        name.match_indices('.')
+            .map(|(i, _)| i)
+            .filter(|&i| !name[..i].trim_start_matches('.').is_empty())
+            .find_map(|i| self.entries.get(&name[i..]))
+            .map(|(mime_type, category)| (mime_type.as_str(), *category))
+    }
+}
+
+static DEFAULT_MIME_TABLE: Lazy<MimeTable> = Lazy::new(MimeTable::with_defaults);
+
+impl FlexPath {
+    /// Returns the MIME type of this path according to its extension,
+    /// using the built-in table of [`MimeTable::with_defaults`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!(Some("image/jpeg"), FlexPath::new_common("photos/a.JPG").mime_type());
+    /// assert_eq!(Some("text/typescript"), FlexPath::new_common("lib.d.ts").mime_type());
+    /// assert_eq!(None, FlexPath::new_common("Makefile").mime_type());
+    /// ```
+    pub fn mime_type(&self) -> Option<&'static str> {
+        DEFAULT_MIME_TABLE.lookup(split_base_name(&self.0).1).map(|(mime_type, _)| mime_type)
+    }
+
+    /// Returns the MIME type of this path according to its extension,
+    /// using the given table.
+    pub fn mime_type_with<'a>(&self, table: &'a MimeTable) -> Option<&'a str> {
+        table.lookup(split_base_name(&self.0).1).map(|(mime_type, _)| mime_type)
+    }
+
+    /// Returns the category of this path according to its extension,
+    /// using the built-in table of [`MimeTable::with_defaults`]. Unknown extensions
+    /// give `FileCategory::Other`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FileCategory, FlexPath};
+    /// assert_eq!(FileCategory::Image, FlexPath::new_common("a.png").file_category());
+    /// assert_eq!(FileCategory::SourceCode, FlexPath::new_common("src/main.rs").file_category());
+    /// assert_eq!(FileCategory::Other, FlexPath::new_common("Makefile").file_category());
+    /// ```
+    pub fn file_category(&self) -> FileCategory {
+        self.file_category_with(&DEFAULT_MIME_TABLE)
+    }
+
+    /// Returns the category of this path according to its extension,
+    /// using the given table.
+    pub fn file_category_with(&self, table: &MimeTable) -> FileCategory {
+        table.lookup(split_base_name(&self.0).1).map_or(FileCategory::Other, |(_, category)| category)
+    }
+}
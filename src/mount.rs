@@ -0,0 +1,98 @@
+/*!
+Translation of paths between a host and a container through
+a table of mounts.
+*/
+
+use super::FlexPath;
+
+/// A table of mounts, each mapping a host directory to a container directory.
+///
+/// Paths are translated through the mount with the longest matching prefix,
+/// comparing whole segments as in [`FlexPath::starts_with`]. Host and container
+/// paths may use different variants, such as a `Windows` host with a `Common` container.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPath, FlexPathVariant, MountTable};
+///
+/// let mut mounts = MountTable::new();
+/// mounts.add(FlexPath::new("C:/work", FlexPathVariant::Windows), FlexPath::new_common("/src"));
+/// mounts.add(FlexPath::new("C:/work/target", FlexPathVariant::Windows), FlexPath::new_common("/build"));
+///
+/// let host = FlexPath::new("C:/work/target/debug/app.d", FlexPathVariant::Windows);
+/// assert_eq!("/build/debug/app.d", mounts.to_container(&host).unwrap().to_string());
+/// assert_eq!("C:/work/lib.rs", mounts.to_host(&FlexPath::new_common("/src/lib.rs")).unwrap().to_string());
+/// assert!(mounts.to_host(&FlexPath::new_common("/usr/include")).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MountTable {
+    mounts: Vec<(FlexPath, FlexPath)>,
+}
+
+impl MountTable {
+    /// Constructs an empty `MountTable`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mount of the `host` directory at the `container` directory.
+    pub fn add(&mut self, host: FlexPath, container: FlexPath) {
+        self.mounts.push((host, container));
+    }
+
+    /// Returns the mounts as `(host, container)` pairs, in insertion order.
+    pub fn mounts(&self) -> &[(FlexPath, FlexPath)] {
+        &self.mounts
+    }
+
+    /// Translates a host path into a container path.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `path` is not under any host directory.
+    pub fn to_container(&self, path: &FlexPath) -> Result<FlexPath, UnmappedPathError> {
+        Self::translate(self.mounts.iter().map(|(host, container)| (host, container)), path)
+    }
+
+    /// Translates a container path into a host path.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `path` is not under any container directory.
+    pub fn to_host(&self, path: &FlexPath) -> Result<FlexPath, UnmappedPathError> {
+        Self::translate(self.mounts.iter().map(|(host, container)| (container, host)), path)
+    }
+
+    fn translate<'a, T>(mounts: T, path: &FlexPath) -> Result<FlexPath, UnmappedPathError>
+        where T: Iterator<Item = (&'a FlexPath, &'a FlexPath)>
+    {
+        // every matching prefix is an ancestor of `path`, so the longest
+        // string is also the one with the most segments.
+        mounts
+            .filter_map(|(from, to)| path.strip_prefix(from).ok().map(|rest| (from, to, rest)))
+            .max_by_key(|(from, _, _)| from.0.len())
+            .map(|(_, to, rest)| to.resolve(&rest))
+            .ok_or_else(|| UnmappedPathError(path.clone()))
+    }
+}
+
+/// An error returned from [`MountTable`] and [`TreeMapping`](crate::TreeMapping)
+/// translations if a path is not under any mapped directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmappedPathError(pub(crate) FlexPath);
+
+impl UnmappedPathError {
+    /// The path that could not be translated.
+    pub fn path(&self) -> &FlexPath {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for UnmappedPathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "path is not under any mapped directory: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnmappedPathError {}
@@ -0,0 +1,255 @@
+/*!
+Object-store keys, where `.`, `..` and empty segments are literal.
+*/
+
+use lazy_regex::*;
+use std::collections::BTreeSet;
+
+/// A key of an object store such as Amazon S3 or Google Cloud Storage.
+///
+/// Unlike a [`FlexPath`](crate::FlexPath), a key is kept as written: there are
+/// no directories, so `a/../b`, `a/./b`, `a//b` and `a/b` are four distinct keys.
+/// Slashes only matter to listings that use them as a delimiter.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::ObjectKey;
+///
+/// let key = ObjectKey::new("logs/../2024//app.log");
+/// assert_eq!(vec!["logs", "..", "2024", "", "app.log"], key.segments().collect::<Vec<_>>());
+/// assert_eq!("app.log", key.name());
+/// assert_ne!(ObjectKey::new("a/b"), ObjectKey::new("a/./b"));
+/// assert_eq!("logs/x/y", ObjectKey::new("logs").join("x/y").as_str());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ObjectKey(String);
+
+impl ObjectKey {
+    /// Constructs a key from its text.
+    pub fn new(key: &str) -> Self {
+        Self(key.to_owned())
+    }
+
+    /// Returns the text of the key.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Iterates over the segments of the key delimited by slashes,
+    /// including empty, `.` and `..` segments.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split('/')
+    }
+
+    /// Returns the text after the last slash.
+    pub fn name(&self) -> &str {
+        self.0.rsplit('/').next().unwrap()
+    }
+
+    /// Indicates whether the key starts with a prefix, compared as text
+    /// as in object-store listings, so `logs/app` starts with `logs/a`.
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.0.starts_with(prefix)
+    }
+
+    /// Appends text to the key, adding a slash between them
+    /// unless the key is empty or already ends with a slash.
+    pub fn join(&self, text: &str) -> ObjectKey {
+        if self.0.is_empty() || self.0.ends_with('/') {
+            Self(self.0.clone() + text)
+        } else {
+            Self(self.0.clone() + "/" + text)
+        }
+    }
+}
+
+impl std::fmt::Display for ObjectKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An object store with its own URL scheme.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub enum ObjectStore {
+    /// Amazon S3, with `s3://` URLs.
+    S3,
+    /// Google Cloud Storage, with `gs://` URLs.
+    Gcs,
+}
+
+impl ObjectStore {
+    /// Returns the URL scheme, without `://`.
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            Self::S3 => "s3",
+            Self::Gcs => "gs",
+        }
+    }
+}
+
+/// An `s3://bucket/key` or `gs://bucket/key` URL.
+///
+/// The key is everything after the slash that follows the bucket, kept
+/// literally and without percent-decoding, as in the command-line tools of both stores.
+/// A URL without a key, such as `s3://bucket` or `s3://bucket/`, has an empty key.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{ObjectStore, ObjectUrl};
+///
+/// let url = ObjectUrl::parse("s3://my-bucket/a/../b.txt").unwrap();
+/// assert_eq!(ObjectStore::S3, url.store());
+/// assert_eq!("my-bucket", url.bucket());
+/// assert_eq!("a/../b.txt", url.key().as_str());
+/// assert_eq!("s3://my-bucket/a/../b.txt", url.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectUrl {
+    store: ObjectStore,
+    bucket: String,
+    key: ObjectKey,
+}
+
+impl ObjectUrl {
+    /// Constructs a URL from its parts.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the bucket name is invalid.
+    pub fn new(store: ObjectStore, bucket: &str, key: ObjectKey) -> Result<Self, ObjectUrlError> {
+        // both stores require lowercase names starting and ending with a letter or digit
+        let valid = match store {
+            // 3 to 63 characters, without underscores
+            ObjectStore::S3 => regex_is_match!(r"^[a-z0-9][a-z0-9.-]{1,61}[a-z0-9]$", bucket),
+            // 3 to 63 characters, or up to 222 characters with dots
+            // separating components of at most 63 characters
+            ObjectStore::Gcs => {
+                regex_is_match!(r"^[a-z0-9][a-z0-9._-]{1,220}[a-z0-9]$", bucket)
+                    && (bucket.len() <= 63 || bucket.split('.').all(|component| component.len() <= 63))
+            },
+        };
+        if !valid {
+            return Err(ObjectUrlError::InvalidBucket(bucket.to_owned()));
+        }
+        Ok(Self { store, bucket: bucket.to_owned(), key })
+    }
+
+    /// Parses an `s3://` or `gs://` URL.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the URL has another scheme or an invalid bucket name.
+    pub fn parse(url: &str) -> Result<Self, ObjectUrlError> {
+        let Some((_, scheme, bucket, key)) = regex_captures!(r"^([A-Za-z0-9]+)://([^/]*)/?(.*)$"s, url) else {
+            return Err(ObjectUrlError::InvalidScheme(url.to_owned()));
+        };
+        let store = match scheme.to_ascii_lowercase().as_str() {
+            "s3" => ObjectStore::S3,
+            "gs" => ObjectStore::Gcs,
+            _ => return Err(ObjectUrlError::InvalidScheme(url.to_owned())),
+        };
+        Self::new(store, bucket, ObjectKey::new(key))
+    }
+
+    /// The object store.
+    pub fn store(&self) -> ObjectStore {
+        self.store
+    }
+
+    /// The bucket name.
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// The key, which is empty for a URL of a bucket.
+    pub fn key(&self) -> &ObjectKey {
+        &self.key
+    }
+}
+
+impl std::fmt::Display for ObjectUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://{}/{}", self.store.scheme(), self.bucket, self.key)
+    }
+}
+
+/// An error returned from [`ObjectUrl::parse`] and [`ObjectUrl::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectUrlError {
+    /// The URL does not start with `s3://` or `gs://`.
+    InvalidScheme(String),
+    /// The bucket name is invalid.
+    InvalidBucket(String),
+}
+
+impl std::fmt::Display for ObjectUrlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidScheme(url) => write!(f, "{:?} is not an s3:// or gs:// URL", url),
+            Self::InvalidBucket(bucket) => write!(f, "invalid bucket name {:?}", bucket),
+        }
+    }
+}
+
+impl std::error::Error for ObjectUrlError {}
+
+/// Lists keys as an object-store listing request would, returning the keys that
+/// start with `prefix`, in ascending order.
+///
+/// With a delimiter, keys whose text after `prefix` contains the delimiter are
+/// rolled up into common prefixes, which end at the first occurrence of
+/// the delimiter after `prefix`, including it.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{list_keys, ObjectKey};
+///
+/// let keys: Vec<ObjectKey> = ["photos/2024/a.jpg", "photos/2024/b.jpg", "photos/cover.jpg", "photos/../x", "readme"]
+///     .iter().map(|k| ObjectKey::new(k)).collect();
+/// let listing = list_keys(&keys, "photos/", Some("/"));
+/// assert_eq!(vec![&ObjectKey::new("photos/cover.jpg")], listing.keys().iter().collect::<Vec<_>>());
+/// assert_eq!(&["photos/../".to_owned(), "photos/2024/".to_owned()], listing.common_prefixes());
+/// ```
+pub fn list_keys<'a, T>(keys: T, prefix: &str, delimiter: Option<&str>) -> ObjectListing
+    where T: IntoIterator<Item = &'a ObjectKey>
+{
+    let mut listed = BTreeSet::new();
+    let mut common_prefixes = BTreeSet::new();
+    for key in keys {
+        let Some(rest) = key.0.strip_prefix(prefix) else {
+            continue;
+        };
+        match delimiter.filter(|d| !d.is_empty()).and_then(|d| rest.find(d).map(|i| i + d.len())) {
+            Some(end) => {
+                common_prefixes.insert(prefix.to_owned() + &rest[..end]);
+            },
+            None => {
+                listed.insert(key.clone());
+            },
+        }
+    }
+    ObjectListing { keys: listed.into_iter().collect(), common_prefixes: common_prefixes.into_iter().collect() }
+}
+
+/// The result of [`list_keys`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ObjectListing {
+    keys: Vec<ObjectKey>,
+    common_prefixes: Vec<String>,
+}
+
+impl ObjectListing {
+    /// The listed keys, in ascending order and without duplicates.
+    pub fn keys(&self) -> &[ObjectKey] {
+        &self.keys
+    }
+
+    /// The common prefixes, in ascending order and without duplicates.
+    pub fn common_prefixes(&self) -> &[String] {
+        &self.common_prefixes
+    }
+}
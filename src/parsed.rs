@@ -0,0 +1,252 @@
+/*!
+Decomposition of paths into root, directory, base name, name and extension,
+compatible with the Node.js `path.parse` and `path.format` functions.
+*/
+
+use super::{FlexPath, FlexPathVariant};
+
+/// The parts of a path, as returned by the Node.js `path.parse` function.
+///
+/// The `Common` variant behaves as `path.posix` and the
+/// `Windows` variant behaves as `path.win32`.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPathVariant, ParsedPath};
+///
+/// let parsed = ParsedPath::new(r"C:\dir\file.txt", FlexPathVariant::Windows);
+/// assert_eq!(r"C:\", parsed.root);
+/// assert_eq!(r"C:\dir", parsed.dir);
+/// assert_eq!("file.txt", parsed.base);
+/// assert_eq!("file", parsed.name);
+/// assert_eq!(".txt", parsed.ext);
+/// assert_eq!(r"C:\dir\file.txt", parsed.format(FlexPathVariant::Windows));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ParsedPath {
+    /// The root, such as `/`, `C:\` or `\\server\share\`.
+    pub root: String,
+    /// The directory, including the root.
+    pub dir: String,
+    /// The last segment, including the extension.
+    pub base: String,
+    /// The last segment, excluding the extension.
+    pub name: String,
+    /// The extension, including the dot prefix.
+    pub ext: String,
+}
+
+impl ParsedPath {
+    /// Parses a path as the Node.js `path.parse` function does.
+    /// The path is not resolved.
+    pub fn new(path: &str, variant: FlexPathVariant) -> Self {
+        match variant {
+            FlexPathVariant::Common => parse_posix(path),
+            FlexPathVariant::Windows => parse_win32(path),
+        }
+    }
+
+    /// Builds a path from its parts as the Node.js `path.format` function does.
+    ///
+    /// `dir` takes precedence over `root`, and `base` takes precedence over
+    /// `name` and `ext`. A lacking dot (`.`) prefix is added to `ext`.
+    pub fn format(&self, variant: FlexPathVariant) -> String {
+        let separator = if variant == FlexPathVariant::Windows { "\\" } else { "/" };
+        let dir = if self.dir.is_empty() { &self.root } else { &self.dir };
+        let base = if self.base.is_empty() {
+            let dot = if self.ext.is_empty() || self.ext.starts_with('.') { "" } else { "." };
+            self.name.clone() + dot + &self.ext
+        } else {
+            self.base.clone()
+        };
+        if dir.is_empty() {
+            base
+        } else if *dir == self.root {
+            dir.clone() + &base
+        } else {
+            dir.clone() + separator + &base
+        }
+    }
+}
+
+impl FlexPath {
+    /// Decomposes this path as the Node.js `path.parse` function does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// let parsed = FlexPath::new_common("/home/user/.bashrc").parse();
+    /// assert_eq!(("/home/user", ".bashrc", ""), (parsed.dir.as_str(), parsed.name.as_str(), parsed.ext.as_str()));
+    /// ```
+    pub fn parse(&self) -> ParsedPath {
+        ParsedPath::new(&self.0, self.1)
+    }
+
+    /// Builds a `FlexPath` from its parts as the Node.js `path.format` function does.
+    /// The resulting path is resolved.
+    pub fn format(parsed: &ParsedPath, variant: FlexPathVariant) -> FlexPath {
+        FlexPath::new(&parsed.format(variant), variant)
+    }
+}
+
+/// Finds the start of the last segment, the end of the last segment
+/// and the start of its extension, scanning backwards down to `root_end`.
+/// This mirrors the scanning loop shared by both Node.js implementations.
+fn scan_base(path: &[u8], root_end: usize, start_part: usize, is_separator: fn(u8) -> bool) -> (usize, Option<usize>, Option<usize>) {
+    let mut start_dot = None;
+    let mut start_part = start_part;
+    let mut end = None;
+    // the state of characters seen before the first dot:
+    // 0 for none, 1 for dots only, -1 for other characters.
+    let mut pre_dot_state = 0;
+
+    for i in (root_end..path.len()).rev() {
+        let code = path[i];
+        if is_separator(code) {
+            // a separator that is not part of the trailing separators
+            // delimits the last segment.
+            if end.is_some() {
+                start_part = i + 1;
+                break;
+            }
+            continue;
+        }
+        if end.is_none() {
+            end = Some(i + 1);
+        }
+        if code == b'.' {
+            if start_dot.is_none() {
+                start_dot = Some(i);
+            } else if pre_dot_state != 1 {
+                pre_dot_state = 1;
+            }
+        } else if start_dot.is_some() {
+            pre_dot_state = -1;
+        }
+    }
+
+    // no extension if there is no dot, if the dot is preceded by
+    // nothing but the segment start, or if the segment is exactly `..`.
+    let has_ext = match (start_dot, end) {
+        (Some(dot), Some(end)) => !(pre_dot_state == 0 || (pre_dot_state == 1 && dot == end - 1 && dot == start_part + 1)),
+        _ => false,
+    };
+    (start_part, end, if has_ext { start_dot } else { None })
+}
+
+fn parse_posix(path: &str) -> ParsedPath {
+    let mut r = ParsedPath::default();
+    let bytes = path.as_bytes();
+    if bytes.is_empty() {
+        return r;
+    }
+    let is_absolute = bytes[0] == b'/';
+    if is_absolute {
+        r.root = "/".into();
+    }
+    let (start_part, end, start_dot) = scan_base(bytes, if is_absolute { 1 } else { 0 }, 0, |c| c == b'/');
+    if let Some(end) = end {
+        let start = if start_part == 0 && is_absolute { 1 } else { start_part };
+        r.base = path[start..end].to_owned();
+        match start_dot {
+            Some(dot) => {
+                r.name = path[start..dot].to_owned();
+                r.ext = path[dot..end].to_owned();
+            },
+            None => r.name = r.base.clone(),
+        }
+    }
+    if start_part > 0 {
+        r.dir = path[..start_part - 1].to_owned();
+    } else if is_absolute {
+        r.dir = "/".into();
+    }
+    r
+}
+
+fn is_win32_separator(code: u8) -> bool {
+    code == b'/' || code == b'\\'
+}
+
+fn parse_win32(path: &str) -> ParsedPath {
+    let mut r = ParsedPath::default();
+    let bytes = path.as_bytes();
+    let len = bytes.len();
+    if len == 0 {
+        return r;
+    }
+    if len == 1 {
+        if is_win32_separator(bytes[0]) {
+            r.root = path.to_owned();
+            r.dir = path.to_owned();
+        } else {
+            r.base = path.to_owned();
+            r.name = path.to_owned();
+        }
+        return r;
+    }
+
+    let mut root_end = 0;
+    if is_win32_separator(bytes[0]) {
+        // possible UNC root
+        root_end = 1;
+        if is_win32_separator(bytes[1]) {
+            let mut j = 2;
+            let mut last = j;
+            while j < len && !is_win32_separator(bytes[j]) {
+                j += 1;
+            }
+            if j < len && j != last {
+                last = j;
+                while j < len && is_win32_separator(bytes[j]) {
+                    j += 1;
+                }
+                if j < len && j != last {
+                    last = j;
+                    while j < len && !is_win32_separator(bytes[j]) {
+                        j += 1;
+                    }
+                    if j == len {
+                        // an UNC root only
+                        root_end = j;
+                    } else if j != last {
+                        // an UNC root with leftovers
+                        root_end = j + 1;
+                    }
+                }
+            }
+        }
+    } else if bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        // possible drive root
+        if len == 2 || (len == 3 && is_win32_separator(bytes[2])) {
+            r.root = path.to_owned();
+            r.dir = path.to_owned();
+            return r;
+        }
+        root_end = if is_win32_separator(bytes[2]) { 3 } else { 2 };
+    }
+    r.root = path[..root_end].to_owned();
+
+    let (start_part, end, start_dot) = scan_base(bytes, root_end, root_end, is_win32_separator);
+    if let Some(end) = end {
+        r.base = path[start_part..end].to_owned();
+        match start_dot {
+            Some(dot) => {
+                r.name = path[start_part..dot].to_owned();
+                r.ext = path[dot..end].to_owned();
+            },
+            None => r.name = r.base.clone(),
+        }
+    }
+
+    // if the directory is the root, the entire root is used as the directory,
+    // including any trailing separator.
+    r.dir = if start_part > 0 && start_part != root_end {
+        path[..start_part - 1].to_owned()
+    } else {
+        r.root.clone()
+    };
+    r
+}
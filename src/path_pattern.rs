@@ -0,0 +1,398 @@
+/*!
+Route-style path patterns with named captures.
+*/
+
+use super::{FlexPath, FlexPathVariant};
+use super::glob::{char_eq, match_spans, path_root, pattern_root, GlobRoot};
+use std::collections::BTreeMap;
+
+/// A route-style pattern, matched against the segments of a [`FlexPath`]
+/// and filled in from captures to produce paths.
+///
+/// Syntax:
+///
+/// - `{name}` captures one or more characters within a segment.
+/// - `:name` captures one or more characters within a segment, as `{name}`.
+///   Names consist of ASCII letters, digits and underscores.
+/// - `*` captures any sequence of characters within a segment.
+/// - `**` as a whole segment captures any number of segments, including none.
+/// - A backslash (`\`) escapes the next character. Patterns always delimit
+///   segments with forward slashes (`/`).
+///
+/// Captures are greedy, so `{name}.{ext}` captures `a.tar` and `gz` from `a.tar.gz`.
+/// A name used twice must capture the same text twice, where each use captures
+/// greedily as if names were distinct. `*` and `**` are
+/// captured by position, as [`PathCaptures::wildcards`].
+///
+/// A pattern starting with `/`, a drive (`C:/`) or `//` only matches paths with
+/// the same root, while any other pattern only matches relative paths.
+/// Paths of the `Windows` variant are matched case-insensitively.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPath, FlexPathVariant, PathPattern};
+///
+/// let pattern = PathPattern::new("assets/{locale}/{name}.{ext}").unwrap();
+/// let captures = pattern.matches(&FlexPath::new_common("assets/en/logo.png")).unwrap();
+/// assert_eq!(Some("en"), captures.get("locale"));
+/// assert_eq!(Some("logo"), captures.get("name"));
+///
+/// let output = PathPattern::new("public/{name}.{locale}.{ext}").unwrap();
+/// assert_eq!("public/logo.en.png", output.fill(&captures, FlexPathVariant::Common).unwrap().to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    pattern: String,
+    root: GlobRoot,
+    root_text: String,
+    segments: Vec<PatternSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PatternSegment {
+    AnyDepth,
+    Tokens(Vec<PatternToken>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PatternToken {
+    Literal(char),
+    Capture(String),
+    Star,
+}
+
+impl PathPattern {
+    /// Parses a path pattern.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pattern has an unclosed `{`, a capture
+    /// with an invalid name or a trailing backslash.
+    pub fn new(pattern: &str) -> Result<Self, PathPatternError> {
+        let (root, rest) = pattern_root(pattern);
+        let root_text = pattern[..pattern.len() - rest.len()].to_owned();
+        let segments = split_segments(rest)
+            .into_iter()
+            .filter(|s| !s.is_empty() && s != ".")
+            .map(|s| if s == "**" { Ok(PatternSegment::AnyDepth) } else { parse_tokens(&s, pattern).map(PatternSegment::Tokens) })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { pattern: pattern.to_owned(), root, root_text, segments })
+    }
+
+    /// Returns the text this pattern was parsed from.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the names of the captures, in pattern order and without repetition.
+    pub fn capture_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        for segment in &self.segments {
+            if let PatternSegment::Tokens(tokens) = segment {
+                for token in tokens {
+                    if let PatternToken::Capture(name) = token {
+                        if !names.contains(&name.as_str()) {
+                            names.push(name);
+                        }
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Returns the number of `*` and `**` wildcards.
+    pub fn wildcard_count(&self) -> usize {
+        self.wildcard_depths().len()
+    }
+
+    /// Indicates, for each wildcard in pattern order, whether it is a `**` wildcard.
+    pub(crate) fn wildcard_depths(&self) -> Vec<bool> {
+        self.segments.iter().flat_map(|segment| match segment {
+            PatternSegment::AnyDepth => vec![true],
+            PatternSegment::Tokens(tokens) => tokens.iter().filter(|token| **token == PatternToken::Star).map(|_| false).collect(),
+        }).collect()
+    }
+
+    /// Matches a path against this pattern, returning its captures.
+    pub fn matches(&self, path: &FlexPath) -> Option<PathCaptures> {
+        let variant = path.variant();
+        let (root, rest) = path_root(&path.0, variant);
+        if !self.root.matches(&root, variant) {
+            return None;
+        }
+        let segments: Vec<&str> = rest.split('/').filter(|s| !s.is_empty()).collect();
+        let case_insensitive = variant == FlexPathVariant::Windows;
+        let tokens_match = |tokens: &[PatternToken], chars: &[char]| {
+            match_spans(tokens, chars, PatternToken::wildcard_min, |token, &c| token.matches_char(c, case_insensitive))
+        };
+        let ranges = match_spans(&self.segments, &segments, |pattern| (*pattern == PatternSegment::AnyDepth).then_some(0), |pattern, segment| match pattern {
+            PatternSegment::AnyDepth => unreachable!(),
+            PatternSegment::Tokens(tokens) => tokens_match(tokens, &segment.chars().collect::<Vec<_>>()).is_some(),
+        })?;
+
+        let mut captures = PathCaptures::new();
+        for (pattern, range) in self.segments.iter().zip(ranges) {
+            let tokens = match pattern {
+                PatternSegment::AnyDepth => {
+                    captures.push_wildcard(&segments[range].join("/"));
+                    continue;
+                },
+                PatternSegment::Tokens(tokens) => tokens,
+            };
+            let chars: Vec<char> = segments[range.start].chars().collect();
+            for (token, span) in tokens.iter().zip(tokens_match(tokens, &chars).expect("the segment matched")) {
+                let value: String = chars[span].iter().collect();
+                match token {
+                    PatternToken::Literal(_) => {},
+                    PatternToken::Capture(name) => {
+                        if captures.get(name).is_some_and(|previous| !text_eq(previous, &value, case_insensitive)) {
+                            return None;
+                        }
+                        captures.insert(name.clone(), &value);
+                    },
+                    PatternToken::Star => captures.push_wildcard(&value),
+                }
+            }
+        }
+        Some(captures)
+    }
+
+    /// Produces a path by replacing the captures of this pattern
+    /// with the values of `captures`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a capture or wildcard has no value, if the value of a capture
+    /// is empty, or if the value of a capture or `*` wildcard contains a separator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexPath, FlexPathVariant, PathPattern};
+    ///
+    /// let captures = PathPattern::new("src/:module/**/*.rs").unwrap()
+    ///     .matches(&FlexPath::new_common("src/net/http/client.rs")).unwrap();
+    /// let path = PathPattern::new("docs/:module/**/*.md").unwrap().fill(&captures, FlexPathVariant::Common).unwrap();
+    /// assert_eq!("docs/net/http/client.md", path.to_string());
+    /// ```
+    pub fn fill(&self, captures: &PathCaptures, variant: FlexPathVariant) -> Result<FlexPath, PathPatternFillError> {
+        let mut segments: Vec<String> = vec![];
+        let mut wildcards = captures.wildcards().iter();
+        let mut wildcard_index = 0;
+        let mut next_wildcard = || {
+            let value = wildcards.next().ok_or(PathPatternFillError::MissingWildcard(wildcard_index));
+            wildcard_index += 1;
+            value
+        };
+        for segment in &self.segments {
+            match segment {
+                PatternSegment::AnyDepth => {
+                    let value = next_wildcard()?;
+                    if !value.is_empty() {
+                        segments.push(value.clone());
+                    }
+                },
+                PatternSegment::Tokens(tokens) => {
+                    let mut s = String::new();
+                    for token in tokens {
+                        match token {
+                            PatternToken::Literal(c) => s.push(*c),
+                            PatternToken::Capture(name) => {
+                                let value = captures.get(name).ok_or_else(|| PathPatternFillError::MissingCapture(name.clone()))?;
+                                if value.is_empty() || has_separator(value, variant) {
+                                    return Err(PathPatternFillError::InvalidValue(value.to_owned()));
+                                }
+                                s.push_str(value);
+                            },
+                            PatternToken::Star => {
+                                let value = next_wildcard()?;
+                                if has_separator(value, variant) {
+                                    return Err(PathPatternFillError::InvalidValue(value.clone()));
+                                }
+                                s.push_str(value);
+                            },
+                        }
+                    }
+                    segments.push(s);
+                },
+            }
+        }
+        Ok(FlexPath::new(&(self.root_text.clone() + &segments.join("/")), variant))
+    }
+}
+
+fn has_separator(value: &str, variant: FlexPathVariant) -> bool {
+    value.contains('/') || (variant == FlexPathVariant::Windows && value.contains('\\'))
+}
+
+impl PatternToken {
+    fn wildcard_min(&self) -> Option<usize> {
+        match self {
+            PatternToken::Literal(_) => None,
+            PatternToken::Capture(_) => Some(1),
+            PatternToken::Star => Some(0),
+        }
+    }
+
+    fn matches_char(&self, c: char, case_insensitive: bool) -> bool {
+        match self {
+            PatternToken::Literal(literal) => char_eq(*literal, c, case_insensitive),
+            _ => true,
+        }
+    }
+}
+
+fn text_eq(a: &str, b: &str, case_insensitive: bool) -> bool {
+    a.chars().count() == b.chars().count() && a.chars().zip(b.chars()).all(|(a, b)| char_eq(a, b, case_insensitive))
+}
+
+/// Splits a pattern by unescaped slashes, keeping escapes.
+fn split_segments(pattern: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let segment = segments.last_mut().unwrap();
+                segment.push('\\');
+                segment.extend(chars.next());
+            },
+            '/' => segments.push(String::new()),
+            _ => segments.last_mut().unwrap().push(c),
+        }
+    }
+    segments
+}
+
+fn parse_tokens(segment: &str, pattern: &str) -> Result<Vec<PatternToken>, PathPatternError> {
+    let chars: Vec<char> = segment.chars().collect();
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                let &c = chars.get(i + 1).ok_or_else(|| PathPatternError::TrailingEscape(pattern.to_owned()))?;
+                tokens.push(PatternToken::Literal(c));
+                i += 2;
+            },
+            '{' => {
+                let length = chars[i + 1..].iter().position(|&c| c == '}').ok_or_else(|| PathPatternError::UnclosedCapture(pattern.to_owned()))?;
+                let name: String = chars[i + 1..i + 1 + length].iter().collect();
+                if name.is_empty() || !name.chars().all(is_name_char) {
+                    return Err(PathPatternError::InvalidCaptureName(name));
+                }
+                tokens.push(PatternToken::Capture(name));
+                i += length + 2;
+            },
+            ':' => {
+                let length = chars[i + 1..].iter().take_while(|&&c| is_name_char(c)).count();
+                if length == 0 {
+                    return Err(PathPatternError::InvalidCaptureName(String::new()));
+                }
+                tokens.push(PatternToken::Capture(chars[i + 1..i + 1 + length].iter().collect()));
+                i += length + 1;
+            },
+            '*' => {
+                // consecutive asterisks within a segment act as one
+                if tokens.last() != Some(&PatternToken::Star) {
+                    tokens.push(PatternToken::Star);
+                }
+                i += 1;
+            },
+            c => {
+                tokens.push(PatternToken::Literal(c));
+                i += 1;
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+/// Captures of a [`PathPattern`] match, used to fill in a pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PathCaptures {
+    named: BTreeMap<String, String>,
+    wildcards: Vec<String>,
+}
+
+impl PathCaptures {
+    /// Constructs empty captures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of a named capture.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.named.get(name).map(|value| value.as_str())
+    }
+
+    /// Sets the value of a named capture.
+    pub fn insert(&mut self, name: impl Into<String>, value: &str) {
+        self.named.insert(name.into(), value.to_owned());
+    }
+
+    /// Iterates over the named captures, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.named.iter().map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the values of the `*` and `**` wildcards, in pattern order.
+    /// The value of a `**` wildcard is its segments joined by forward slashes.
+    pub fn wildcards(&self) -> &[String] {
+        &self.wildcards
+    }
+
+    /// Appends the value of the next wildcard.
+    pub fn push_wildcard(&mut self, value: &str) {
+        self.wildcards.push(value.to_owned());
+    }
+}
+
+/// An error returned from [`PathPattern::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathPatternError {
+    /// The pattern has a `{` without a closing `}`.
+    UnclosedCapture(String),
+    /// A capture has an empty name or a name with invalid characters.
+    InvalidCaptureName(String),
+    /// The pattern ends with a backslash.
+    TrailingEscape(String),
+}
+
+impl std::fmt::Display for PathPatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnclosedCapture(pattern) => write!(f, "unclosed capture in pattern {:?}", pattern),
+            Self::InvalidCaptureName(name) => write!(f, "invalid capture name {:?}", name),
+            Self::TrailingEscape(pattern) => write!(f, "trailing backslash in pattern {:?}", pattern),
+        }
+    }
+}
+
+impl std::error::Error for PathPatternError {}
+
+/// An error returned from [`PathPattern::fill`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathPatternFillError {
+    /// A named capture has no value.
+    MissingCapture(String),
+    /// A wildcard, given by its position, has no value.
+    MissingWildcard(usize),
+    /// A value is empty or contains a separator where a single segment is expected.
+    InvalidValue(String),
+}
+
+impl std::fmt::Display for PathPatternFillError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingCapture(name) => write!(f, "missing value for capture {:?}", name),
+            Self::MissingWildcard(index) => write!(f, "missing value for wildcard {}", index),
+            Self::InvalidValue(value) => write!(f, "invalid segment value {:?}", value),
+        }
+    }
+}
+
+impl std::error::Error for PathPatternFillError {}
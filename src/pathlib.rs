@@ -0,0 +1,162 @@
+/*!
+Methods mirroring Python's `PurePath`, such as `with_name` and `relative_to`.
+*/
+
+use super::{flexible, split_base_name, FlexPath, FlexPathVariant, StripPrefixError};
+use lazy_regex::*;
+
+impl FlexPath {
+    /// Returns a new path with the file name changed, as in Python's `PurePath.with_name`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if this path has an empty name, such as `/`, `C:/` or `\\server/share`,
+    /// or if `name` is not a single segment. Unlike Python, the names `.` and `..`
+    /// are rejected, since a `FlexPath` is always resolved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!("a/c.md", FlexPath::new_common("a/b.txt").with_name("c.md").unwrap().to_string());
+    /// assert!(FlexPath::new_common("/").with_name("c").is_err());
+    /// assert!(FlexPath::new_common("a/b").with_name("c/d").is_err());
+    /// ```
+    pub fn with_name(&self, name: &str) -> Result<FlexPath, PathNameError> {
+        if self.pathlib_name().is_empty() {
+            return Err(PathNameError::EmptyName(self.clone()));
+        }
+        let invalid = name.is_empty()
+            || name == "."
+            || name == ".."
+            || name.contains(['/', '\\'])
+            || (self.1 == FlexPathVariant::Windows && regex_is_match!(r"^[A-Za-z]:", name));
+        if invalid {
+            return Err(PathNameError::InvalidName(name.to_owned()));
+        }
+        Ok(FlexPath(split_base_name(&self.0).0.to_owned() + name, self.1))
+    }
+
+    /// Returns a new path with the stem of the file name changed,
+    /// keeping its suffix, as in Python's `PurePath.with_stem`.
+    ///
+    /// # Errors
+    ///
+    /// Fails as in [`.with_name`], or if `stem` is empty while the file name has a suffix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!("a/c.txt", FlexPath::new_common("a/b.txt").with_stem("c").unwrap().to_string());
+    /// assert_eq!("a/c.tar.gz", FlexPath::new_common("a/b.tar.gz").with_stem("c.tar").unwrap().to_string());
+    /// ```
+    pub fn with_stem(&self, stem: &str) -> Result<FlexPath, PathNameError> {
+        let name = self.pathlib_name();
+        let suffix = &name[python_suffix_start(name).unwrap_or(name.len())..];
+        if stem.is_empty() && !suffix.is_empty() {
+            return Err(PathNameError::NonEmptySuffix(self.clone()));
+        }
+        self.with_name(&(stem.to_owned() + suffix))
+    }
+
+    /// Returns a new path with the suffix of the file name changed, as in Python's
+    /// `PurePath.with_suffix`. An empty `suffix` removes the suffix.
+    ///
+    /// As in Python, the suffix is the part of the file name from its last dot,
+    /// excluding a leading dot and a trailing dot.
+    ///
+    /// # Errors
+    ///
+    /// Fails if this path has an empty name, or if `suffix` does not start
+    /// with a dot, is solely a dot, or contains a path separator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::FlexPath;
+    /// assert_eq!("a/b.tar.zip", FlexPath::new_common("a/b.tar.gz").with_suffix(".zip").unwrap().to_string());
+    /// assert_eq!("a/b", FlexPath::new_common("a/b.txt").with_suffix("").unwrap().to_string());
+    /// assert!(FlexPath::new_common("a/b.txt").with_suffix("zip").is_err());
+    /// ```
+    pub fn with_suffix(&self, suffix: &str) -> Result<FlexPath, PathNameError> {
+        if suffix.contains(['/', '\\']) || (!suffix.is_empty() && !suffix.starts_with('.')) || suffix == "." {
+            return Err(PathNameError::InvalidSuffix(suffix.to_owned()));
+        }
+        let name = self.pathlib_name();
+        if name.is_empty() {
+            return Err(PathNameError::EmptyName(self.clone()));
+        }
+        let stem = &name[..python_suffix_start(name).unwrap_or(name.len())];
+        Ok(FlexPath(split_base_name(&self.0).0.to_owned() + stem + suffix, self.1))
+    }
+
+    /// Indicates whether this path is relative to `base`, as in Python's
+    /// `PurePath.is_relative_to`. This is equivalent to [`.starts_with`].
+    pub fn is_relative_to(&self, base: &FlexPath) -> bool {
+        self.starts_with(base)
+    }
+
+    /// Returns this path relative to `base`, as in Python's `PurePath.relative_to`.
+    ///
+    /// Paths are compared as in [`.starts_with`], so the `Windows` variant compares
+    /// paths case-insensitively as `PureWindowsPath` does. Where Python returns `.`,
+    /// this method returns an empty path.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `base` is not this path or one of its ancestors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexPath, FlexPathVariant};
+    /// let windows = FlexPathVariant::Windows;
+    /// assert_eq!("B/c", FlexPath::new("C:/A/B/c", windows).relative_to(&FlexPath::new("c:/a", windows)).unwrap().to_string());
+    /// assert!(FlexPath::new_common("/a/b").relative_to(&FlexPath::new_common("/c")).is_err());
+    /// ```
+    pub fn relative_to(&self, base: &FlexPath) -> Result<FlexPath, StripPrefixError> {
+        Ok(FlexPath(self.strip_prefix(base)?, self.1))
+    }
+
+    /// Returns the file name as Python's `PurePath.name` does, which is empty
+    /// for the drive and UNC share of the `Windows` variant.
+    fn pathlib_name(&self) -> &str {
+        if self.1 == FlexPathVariant::Windows && self.0[flexible::windows_prefix(&self.0).len()..].trim_start_matches('/').is_empty() {
+            return "";
+        }
+        split_base_name(&self.0).1
+    }
+}
+
+/// Returns the index where the suffix of a file name starts, as in Python's `PurePath.suffix`.
+fn python_suffix_start(name: &str) -> Option<usize> {
+    name.rfind('.').filter(|&i| 0 < i && i < name.len() - 1)
+}
+
+/// An error returned from [`FlexPath::with_name`], [`FlexPath::with_stem`]
+/// and [`FlexPath::with_suffix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathNameError {
+    /// The path has an empty name.
+    EmptyName(FlexPath),
+    /// The given name is not a single segment.
+    InvalidName(String),
+    /// The given suffix is invalid.
+    InvalidSuffix(String),
+    /// The given stem is empty while the path has a non-empty suffix.
+    NonEmptySuffix(FlexPath),
+}
+
+impl std::fmt::Display for PathNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyName(path) => write!(f, "{:?} has an empty name", path.to_string()),
+            Self::InvalidName(name) => write!(f, "invalid name {:?}", name),
+            Self::InvalidSuffix(suffix) => write!(f, "invalid suffix {:?}", suffix),
+            Self::NonEmptySuffix(path) => write!(f, "{:?} has a non-empty suffix", path.to_string()),
+        }
+    }
+}
+
+impl std::error::Error for PathNameError {}
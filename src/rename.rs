@@ -0,0 +1,253 @@
+/*!
+Planning of batch renames from glob captures.
+*/
+
+use super::{FlexGlob, FlexPath, FlexPathVariant, GlobError};
+use lazy_regex::*;
+use std::collections::{HashMap, HashSet};
+
+/// Plans renaming every path matching a glob pattern into the path produced
+/// by a template, without touching the file system.
+///
+/// The template refers to the captures of the pattern, as returned by
+/// [`FlexGlob::captures`], by their position starting from 1, as `#1` or `{1}`.
+/// Other text is copied, and the result is resolved with the variant of the
+/// renamed path. Paths that do not match, or whose new path is equal, are not renamed.
+///
+/// Every given path is considered to exist, so a rename cannot target a path that
+/// is not renamed itself. Renames that target each other's paths, such as swaps,
+/// go through a temporary name in the same directory.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{plan_renames, FlexPath};
+///
+/// let paths = [FlexPath::new_common("img/a.jpeg"), FlexPath::new_common("img/b.jpeg")];
+/// let plan = plan_renames(&paths, "img/*.jpeg", "img/#1.jpg").unwrap();
+/// assert_eq!(&[
+///     (FlexPath::new_common("img/a.jpeg"), FlexPath::new_common("img/a.jpg")),
+///     (FlexPath::new_common("img/b.jpeg"), FlexPath::new_common("img/b.jpg")),
+/// ], plan.renames());
+/// ```
+///
+/// # Errors
+///
+/// Fails if the pattern is invalid, if the template refers to a missing capture,
+/// if two paths are renamed into the same path, or if a path is renamed into a
+/// given path that is not renamed.
+pub fn plan_renames<'a, T>(paths: T, from_pattern: &str, to_template: &str) -> Result<RenamePlan, RenameError>
+    where T: IntoIterator<Item = &'a FlexPath>
+{
+    let glob = FlexGlob::new(from_pattern).map_err(RenameError::InvalidPattern)?;
+    let template = parse_template(to_template);
+
+    let mut existing = HashSet::new();
+    let mut renames: Vec<(FlexPath, FlexPath)> = vec![];
+    for path in paths {
+        if !existing.insert(path_key(path)) {
+            continue;
+        }
+        let Some(captures) = glob.captures(path) else {
+            continue;
+        };
+        let mut target = String::new();
+        for part in &template {
+            match part {
+                TemplatePart::Literal(s) => target.push_str(s),
+                TemplatePart::Capture(index) => {
+                    let capture = index.checked_sub(1).and_then(|i| captures.get(i))
+                        .ok_or_else(|| RenameError::MissingCapture { path: path.clone(), index: *index })?;
+                    target.push_str(capture);
+                },
+            }
+        }
+        let target = FlexPath::new(&target, path.variant());
+        if target != *path {
+            renames.push((path.clone(), target));
+        }
+    }
+
+    // at most one rename targets a path, so renames form chains and cycles
+    let sources: HashMap<String, usize> = renames.iter().enumerate().map(|(i, (from, _))| (path_key(from), i)).collect();
+    let mut targets: HashMap<String, usize> = HashMap::new();
+    let mut blocker: Vec<Option<usize>> = vec![None; renames.len()];
+    for (i, (from, to)) in renames.iter().enumerate() {
+        let key = path_key(to);
+        if let Some(&other) = targets.get(&key) {
+            return Err(RenameError::Collision { target: to.clone(), sources: [renames[other].0.clone(), from.clone()] });
+        }
+        match sources.get(&key) {
+            Some(&j) if j != i => blocker[i] = Some(j),
+            Some(_) => {},
+            None if existing.contains(&key) => {
+                return Err(RenameError::TargetExists { source: from.clone(), target: to.clone() });
+            },
+            None => {},
+        }
+        targets.insert(key, i);
+    }
+
+    let mut occupied: HashSet<String> = existing.into_iter().chain(targets.into_keys()).collect();
+    let mut operations = vec![];
+    let mut cycles = vec![];
+    let mut done = vec![false; renames.len()];
+    for start in 0..renames.len() {
+        // follow the renames that must run first
+        let mut walk = vec![];
+        let mut i = start;
+        let cycle = loop {
+            if done[i] {
+                break false;
+            }
+            if walk.contains(&i) {
+                break true;
+            }
+            walk.push(i);
+            match blocker[i] {
+                Some(j) => i = j,
+                None => break false,
+            }
+        };
+        if cycle {
+            // move the first path of the cycle aside, run the others
+            // backwards and move the first path into place last
+            let (from, to) = &renames[walk[0]];
+            let temporary = temporary_path(from, &mut occupied);
+            operations.push((from.clone(), temporary.clone()));
+            for &i in walk[1..].iter().rev() {
+                operations.push(renames[i].clone());
+            }
+            operations.push((temporary, to.clone()));
+            cycles.push(walk.clone());
+        } else {
+            for &i in walk.iter().rev() {
+                operations.push(renames[i].clone());
+            }
+        }
+        for i in walk {
+            done[i] = true;
+        }
+    }
+
+    Ok(RenamePlan { renames, operations, cycles })
+}
+
+enum TemplatePart {
+    Literal(String),
+    Capture(usize),
+}
+
+fn parse_template(template: &str) -> Vec<TemplatePart> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        let placeholder = match c {
+            '#' => regex_captures!(r"^#(\d+)", rest),
+            '{' => regex_captures!(r"^\{(\d+)\}", rest),
+            _ => None,
+        };
+        match placeholder.and_then(|(whole, index)| index.parse().ok().map(|index| (whole.len(), index))) {
+            Some((length, index)) => {
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(TemplatePart::Capture(index));
+                rest = &rest[length..];
+            },
+            None => {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            },
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    parts
+}
+
+/// Returns a key identifying a path, comparing paths
+/// of the `Windows` variant case-insensitively.
+fn path_key(path: &FlexPath) -> String {
+    match path.variant() {
+        FlexPathVariant::Common => path.0.clone(),
+        FlexPathVariant::Windows => path.0.to_lowercase(),
+    }
+}
+
+/// Returns an unoccupied path next to `path`, and occupies it.
+fn temporary_path(path: &FlexPath, occupied: &mut HashSet<String>) -> FlexPath {
+    let name = path.base_name();
+    (0..)
+        .map(|n| path.resolve(&format!("../{}.tmp{}", name, if n == 0 { String::new() } else { n.to_string() })))
+        .find(|temporary| occupied.insert(path_key(temporary)))
+        .unwrap()
+}
+
+/// Renames planned by [`plan_renames`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamePlan {
+    renames: Vec<(FlexPath, FlexPath)>,
+    operations: Vec<(FlexPath, FlexPath)>,
+    cycles: Vec<Vec<usize>>,
+}
+
+impl RenamePlan {
+    /// The `(old, new)` path pairs, in the order of the given paths.
+    pub fn renames(&self) -> &[(FlexPath, FlexPath)] {
+        &self.renames
+    }
+
+    /// The `(old, new)` renames to perform, in an order where no rename
+    /// overwrites a path before it is renamed, including renames into and
+    /// from temporary names.
+    pub fn operations(&self) -> &[(FlexPath, FlexPath)] {
+        &self.operations
+    }
+
+    /// The cycles of renames that target each other's paths, as indices into
+    /// [`.renames`]. A cycle of two renames is a swap.
+    ///
+    /// [`.renames`]: RenamePlan::renames
+    pub fn cycles(&self) -> &[Vec<usize>] {
+        &self.cycles
+    }
+}
+
+/// An error returned from [`plan_renames`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// The pattern is invalid.
+    InvalidPattern(GlobError),
+    /// The template refers to a capture, by its position, that the pattern
+    /// does not capture for a path.
+    MissingCapture {
+        path: FlexPath,
+        index: usize,
+    },
+    /// Two paths are renamed into the same path.
+    Collision {
+        target: FlexPath,
+        sources: [FlexPath; 2],
+    },
+    /// A path is renamed into a given path that is not renamed.
+    TargetExists {
+        source: FlexPath,
+        target: FlexPath,
+    },
+}
+
+impl std::fmt::Display for RenameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPattern(error) => write!(f, "{}", error),
+            Self::MissingCapture { path, index } => write!(f, "no capture #{} for {}", index, path),
+            Self::Collision { target, sources } => write!(f, "{} and {} are both renamed into {}", sources[0], sources[1], target),
+            Self::TargetExists { source, target } => write!(f, "{} is renamed into existing path {}", source, target),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
@@ -0,0 +1,295 @@
+/*!
+Rule-based rewriting of paths.
+*/
+
+use super::{FlexPath, FlexPathVariant, PathPattern, PathPatternError, PathPatternFillError};
+use std::collections::HashMap;
+
+/// A rule of a [`PathRewriter`], rewriting paths matching a [`PathPattern`]
+/// into a path produced by filling in an output pattern with the captures.
+///
+/// The output pattern may only use captures of the match pattern, and
+/// its `*` and `**` wildcards take the values of the wildcards of the match
+/// pattern at the same positions.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPath, RewriteRule};
+///
+/// let rule = RewriteRule::new("docs/{name}.md", "site/{name}/index").unwrap().with_extension("html");
+/// assert_eq!("site/a/index.html", rule.rewrite(&FlexPath::new_common("docs/a.md")).unwrap().unwrap().to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    from: PathPattern,
+    to: PathPattern,
+    extension: Option<String>,
+}
+
+impl RewriteRule {
+    /// Constructs a rule from a match pattern and an output pattern.
+    ///
+    /// # Errors
+    ///
+    /// Fails if either pattern is invalid, if the output pattern uses a
+    /// capture that the match pattern does not have, or if the output pattern
+    /// has a wildcard without a counterpart in the match pattern. A `*` wildcard
+    /// in the output pattern requires a `*` wildcard in the match pattern.
+    pub fn new(from: &str, to: &str) -> Result<Self, RewriteRuleError> {
+        let from = PathPattern::new(from).map_err(RewriteRuleError::InvalidPattern)?;
+        let to = PathPattern::new(to).map_err(RewriteRuleError::InvalidPattern)?;
+        let names = from.capture_names();
+        if let Some(name) = to.capture_names().into_iter().find(|name| !names.contains(name)) {
+            return Err(RewriteRuleError::UnknownCapture(name.to_owned()));
+        }
+        let from_depths = from.wildcard_depths();
+        for (i, any_depth) in to.wildcard_depths().into_iter().enumerate() {
+            let unmatched = match from_depths.get(i) {
+                Some(&from_any_depth) => from_any_depth && !any_depth,
+                None => true,
+            };
+            if unmatched {
+                return Err(RewriteRuleError::UnmatchedWildcard(i));
+            }
+        }
+        Ok(Self { from, to, extension: None })
+    }
+
+    /// Changes the extension of the rewritten paths, as [`FlexPath::change_extension`].
+    pub fn with_extension(mut self, extension: &str) -> Self {
+        self.extension = Some(extension.to_owned());
+        self
+    }
+
+    /// The match pattern.
+    pub fn from(&self) -> &PathPattern {
+        &self.from
+    }
+
+    /// The output pattern.
+    pub fn to(&self) -> &PathPattern {
+        &self.to
+    }
+
+    /// The extension given to the rewritten paths, if any.
+    pub fn extension(&self) -> Option<&str> {
+        self.extension.as_deref()
+    }
+
+    /// Rewrites a path if it matches this rule, returning `None` otherwise.
+    /// The result has the variant of `path`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the output pattern cannot be filled in from the captures,
+    /// as in [`PathPattern::fill`].
+    pub fn rewrite(&self, path: &FlexPath) -> Result<Option<FlexPath>, PathPatternFillError> {
+        let Some(captures) = self.from.matches(path) else {
+            return Ok(None);
+        };
+        let output = self.to.fill(&captures, path.variant())?;
+        Ok(Some(match &self.extension {
+            Some(extension) => output.change_extension(extension),
+            None => output,
+        }))
+    }
+}
+
+/// An ordered list of [`RewriteRule`]s, where the first matching rule
+/// rewrites a path.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPath, PathRewriter, RewriteRule};
+///
+/// let mut rewriter = PathRewriter::new();
+/// rewriter.add(RewriteRule::new("src/**/*.d.ts", "types/**/*.d.ts").unwrap());
+/// rewriter.add(RewriteRule::new("src/**/*.ts", "dist/**/*.js").unwrap());
+///
+/// let rewrite = rewriter.rewrite(&FlexPath::new_common("src/a/b.ts")).unwrap().unwrap();
+/// assert_eq!("dist/a/b.js", rewrite.path().to_string());
+/// assert_eq!(1, rewrite.rule());
+/// assert_eq!(Ok(None), rewriter.rewrite(&FlexPath::new_common("lib/b.ts")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PathRewriter {
+    rules: Vec<RewriteRule>,
+}
+
+impl PathRewriter {
+    /// Constructs a `PathRewriter` without rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule after the existing rules, returning its index.
+    pub fn add(&mut self, rule: RewriteRule) -> usize {
+        self.rules.push(rule);
+        self.rules.len() - 1
+    }
+
+    /// Returns the rules, in order.
+    pub fn rules(&self) -> &[RewriteRule] {
+        &self.rules
+    }
+
+    /// Rewrites a path with the first matching rule, returning `None`
+    /// if no rule matches.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the output pattern of the matching rule cannot be filled in,
+    /// as in [`RewriteRule::rewrite`].
+    pub fn rewrite(&self, path: &FlexPath) -> Result<Option<Rewrite>, PathPatternFillError> {
+        for (rule, r) in self.rules.iter().enumerate() {
+            if let Some(output) = r.rewrite(path)? {
+                return Ok(Some(Rewrite { rule, path: output }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Rewrites every path of a set, in order, with `None` for
+    /// the paths that no rule matches.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a path cannot be rewritten, as in [`.rewrite`], or if two
+    /// different paths are rewritten into the same path. Paths of the `Windows`
+    /// variant are compared case-insensitively.
+    ///
+    /// [`.rewrite`]: PathRewriter::rewrite
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::{FlexPath, PathRewriter, RewriteError, RewriteRule};
+    ///
+    /// let mut rewriter = PathRewriter::new();
+    /// rewriter.add(RewriteRule::new("{dir}/*.md", "site/*.html").unwrap());
+    /// let paths = [FlexPath::new_common("a/index.md"), FlexPath::new_common("b/index.md")];
+    /// let Err(RewriteError::Collision(collision)) = rewriter.rewrite_all(&paths) else { panic!() };
+    /// assert_eq!("site/index.html", collision.output().to_string());
+    /// ```
+    pub fn rewrite_all<'a, T>(&self, paths: T) -> Result<Vec<Option<Rewrite>>, RewriteError>
+        where T: IntoIterator<Item = &'a FlexPath>
+    {
+        let mut outputs: HashMap<String, (&FlexPath, usize)> = HashMap::new();
+        let mut rewrites = vec![];
+        for path in paths {
+            let rewrite = self.rewrite(path).map_err(RewriteError::Fill)?;
+            if let Some(rewrite) = &rewrite {
+                let key = match rewrite.path.variant() {
+                    FlexPathVariant::Common => rewrite.path.0.clone(),
+                    FlexPathVariant::Windows => rewrite.path.0.to_lowercase(),
+                };
+                match outputs.get(&key) {
+                    Some(&(other, other_rule)) if other != path => {
+                        return Err(RewriteError::Collision(RewriteCollisionError {
+                            output: rewrite.path.clone(),
+                            sources: [(other.clone(), other_rule), (path.clone(), rewrite.rule)],
+                        }));
+                    },
+                    _ => {
+                        outputs.insert(key, (path, rewrite.rule));
+                    },
+                }
+            }
+            rewrites.push(rewrite);
+        }
+        Ok(rewrites)
+    }
+}
+
+/// A path rewritten by a [`PathRewriter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewrite {
+    rule: usize,
+    path: FlexPath,
+}
+
+impl Rewrite {
+    /// The index of the rule that rewrote the path.
+    pub fn rule(&self) -> usize {
+        self.rule
+    }
+
+    /// The rewritten path.
+    pub fn path(&self) -> &FlexPath {
+        &self.path
+    }
+}
+
+/// An error returned from [`RewriteRule::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteRuleError {
+    /// A pattern is invalid.
+    InvalidPattern(PathPatternError),
+    /// The output pattern uses a capture that the match pattern does not have.
+    UnknownCapture(String),
+    /// A wildcard of the output pattern, given by its position,
+    /// has no counterpart in the match pattern.
+    UnmatchedWildcard(usize),
+}
+
+impl std::fmt::Display for RewriteRuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPattern(error) => write!(f, "{}", error),
+            Self::UnknownCapture(name) => write!(f, "unknown capture {:?} in output pattern", name),
+            Self::UnmatchedWildcard(index) => write!(f, "wildcard {} of output pattern has no counterpart", index),
+        }
+    }
+}
+
+impl std::error::Error for RewriteRuleError {}
+
+/// An error returned from [`PathRewriter::rewrite_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    /// A path cannot be rewritten, as in [`PathRewriter::rewrite`].
+    Fill(PathPatternFillError),
+    /// Two paths are rewritten into the same path.
+    Collision(RewriteCollisionError),
+}
+
+impl std::fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fill(error) => write!(f, "{}", error),
+            Self::Collision(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+/// A collision of two paths rewritten into the same path,
+/// as in [`RewriteError::Collision`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteCollisionError {
+    output: FlexPath,
+    sources: [(FlexPath, usize); 2],
+}
+
+impl RewriteCollisionError {
+    /// The path both paths are rewritten into.
+    pub fn output(&self) -> &FlexPath {
+        &self.output
+    }
+
+    /// The colliding paths, in input order, with the index of the rule that rewrote each one.
+    pub fn sources(&self) -> &[(FlexPath, usize); 2] {
+        &self.sources
+    }
+}
+
+impl std::fmt::Display for RewriteCollisionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} and {} are both rewritten into {}", self.sources[0].0, self.sources[1].0, self.output)
+    }
+}
+
+impl std::error::Error for RewriteCollisionError {}
@@ -0,0 +1,109 @@
+/*!
+Mapping of a source tree into an output tree.
+*/
+
+use super::{extension_arg, matching_extension_start, split_base_name, ExtensionMatching, FlexPath, UnmappedPathError};
+
+/// Maps paths under a source directory to paths under an output directory,
+/// keeping the segments that follow the directory and changing extensions
+/// according to an extension map.
+///
+/// The extension map holds `(source, output)` extension pairs, with or without
+/// a leading dot, such as `("scss", "css")` or `(".d.ts", ".d.js")`. The longest
+/// matching extension is replaced, preferring the first pair if several pairs
+/// have the same extension, so mapping an output path back to its source uses
+/// the first pair with that output extension. Extensions are matched as in
+/// [`FlexPath::has_extension`], and an empty extension removes the extension.
+///
+/// The roots may have different variants. Source paths are compared with
+/// `src_root` according to their own variant, and mapped paths have the
+/// variant of `dst_root`; the same applies in reverse.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::{FlexPath, FlexPathVariant, TreeMapping};
+///
+/// let mapping = TreeMapping {
+///     src_root: FlexPath::new(r"C:\project\styles", FlexPathVariant::Windows),
+///     dst_root: FlexPath::new_common("/srv/www/css"),
+///     extension_map: vec![("scss".into(), "css".into())],
+/// };
+/// let output = mapping.to_dst(&FlexPath::new(r"C:\project\styles\ui\Button.SCSS", FlexPathVariant::Windows)).unwrap();
+/// assert_eq!("/srv/www/css/ui/Button.css", output.to_string());
+/// assert_eq!(r"C:\project\styles\ui\Button.scss", mapping.to_src(&output).unwrap().to_string_with_flex_separator());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeMapping {
+    /// The source directory.
+    pub src_root: FlexPath,
+    /// The output directory.
+    pub dst_root: FlexPath,
+    /// `(source, output)` extension pairs.
+    pub extension_map: Vec<(String, String)>,
+}
+
+impl TreeMapping {
+    /// Constructs a `TreeMapping` without extension changes.
+    pub fn new(src_root: FlexPath, dst_root: FlexPath) -> Self {
+        Self { src_root, dst_root, extension_map: vec![] }
+    }
+
+    /// Adds a `(source, output)` extension pair after the existing pairs.
+    pub fn add_extension(&mut self, src_extension: &str, dst_extension: &str) {
+        self.extension_map.push((src_extension.to_owned(), dst_extension.to_owned()));
+    }
+
+    /// Maps a source path to its output path.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `path` is not under `src_root`.
+    pub fn to_dst(&self, path: &FlexPath) -> Result<FlexPath, UnmappedPathError> {
+        let pairs = self.extension_map.iter().map(|(src, dst)| (src.as_str(), dst.as_str()));
+        Self::translate(path, &self.src_root, &self.dst_root, pairs)
+    }
+
+    /// Maps an output path back to its source path.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `path` is not under `dst_root`.
+    pub fn to_src(&self, path: &FlexPath) -> Result<FlexPath, UnmappedPathError> {
+        let pairs = self.extension_map.iter().map(|(src, dst)| (dst.as_str(), src.as_str()));
+        Self::translate(path, &self.dst_root, &self.src_root, pairs)
+    }
+
+    /// Maps source paths to their output paths, in order.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first path that is not under `src_root`.
+    pub fn to_dst_all<'a, T: IntoIterator<Item = &'a FlexPath>>(&self, paths: T) -> Result<Vec<FlexPath>, UnmappedPathError> {
+        paths.into_iter().map(|path| self.to_dst(path)).collect()
+    }
+
+    /// Maps output paths back to their source paths, in order.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first path that is not under `dst_root`.
+    pub fn to_src_all<'a, T: IntoIterator<Item = &'a FlexPath>>(&self, paths: T) -> Result<Vec<FlexPath>, UnmappedPathError> {
+        paths.into_iter().map(|path| self.to_src(path)).collect()
+    }
+
+    fn translate<'a, T>(path: &FlexPath, from_root: &FlexPath, to_root: &FlexPath, pairs: T) -> Result<FlexPath, UnmappedPathError>
+        where T: Iterator<Item = (&'a str, &'a str)>
+    {
+        let rest = path.strip_prefix(from_root).map_err(|_| UnmappedPathError(path.clone()))?;
+        let matching = ExtensionMatching::for_variant(path.variant());
+        let (dir, name) = split_base_name(&rest);
+        // the earliest start is the longest extension, and `min_by_key` keeps the first pair among equals
+        let rest = match pairs.filter_map(|(from, to)| matching_extension_start(name, from, matching).map(|i| (i, to))).min_by_key(|(i, _)| *i) {
+            Some((i, "")) => dir.to_owned() + &name[..i],
+            Some((i, to)) => dir.to_owned() + &name[..i] + &extension_arg(to),
+            None => rest,
+        };
+        Ok(to_root.resolve(&rest))
+    }
+}
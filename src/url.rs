@@ -0,0 +1,316 @@
+/*!
+URL references following RFC 3986.
+*/
+
+use lazy_regex::*;
+
+/// A URL or relative reference, split into its components as in
+/// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986).
+///
+/// Unlike [`FlexPath`](crate::FlexPath), references keep their query and fragment,
+/// and are resolved as in section 5.2 of the RFC, so a base path without
+/// a trailing slash contributes its directory, not its last segment.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::UrlReference;
+///
+/// let page = UrlReference::parse("https://example.com/blog/post/index.html");
+/// let image = page.resolve(&UrlReference::parse("../img/a.png?x#y"));
+/// assert_eq!("https://example.com/blog/img/a.png?x#y", image.to_string());
+///
+/// let other = UrlReference::parse("https://example.com/blog/img/b.png#z");
+/// assert_eq!("../img/b.png#z", page.relative(&other).to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct UrlReference {
+    scheme: Option<String>,
+    authority: Option<String>,
+    path: String,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+impl UrlReference {
+    /// Splits a reference into its components, as in appendix B of the RFC.
+    /// Every string is a reference, so this does not fail.
+    pub fn parse(text: &str) -> Self {
+        let captures = regex!(r"^(?:([^:/?#]+):)?(?://([^/?#]*))?([^?#]*)(?:\?([^#]*))?(?:#(.*))?$"s).captures(text).unwrap();
+        let component = |i: usize| captures.get(i).map(|m| m.as_str().to_owned());
+        Self {
+            scheme: component(1),
+            authority: component(2),
+            path: component(3).unwrap_or_default(),
+            query: component(4),
+            fragment: component(5),
+        }
+    }
+
+    /// The scheme, such as `https`.
+    pub fn scheme(&self) -> Option<&str> {
+        self.scheme.as_deref()
+    }
+
+    /// The authority, such as `example.com:8080`.
+    pub fn authority(&self) -> Option<&str> {
+        self.authority.as_deref()
+    }
+
+    /// The path, which may be empty.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The query, without the leading `?`.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// The fragment, without the leading `#`.
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// Resolves a reference against this reference as the base, as in
+    /// section 5.2.2 of the RFC.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::UrlReference;
+    ///
+    /// let base = UrlReference::parse("http://a/b/c/d;p?q");
+    /// let resolve = |r: &str| base.resolve(&UrlReference::parse(r)).to_string();
+    /// assert_eq!("http://a/b/c/g", resolve("g"));
+    /// assert_eq!("http://a/b/c/d;p?y", resolve("?y"));
+    /// assert_eq!("http://a/b/c/d;p?q#s", resolve("#s"));
+    /// assert_eq!("http://a/g", resolve("../../../g"));
+    /// assert_eq!("http://g", resolve("//g"));
+    /// ```
+    pub fn resolve(&self, reference: &UrlReference) -> UrlReference {
+        let r = reference;
+        let (scheme, authority, path, query);
+        if r.scheme.is_some() {
+            scheme = r.scheme.clone();
+            authority = r.authority.clone();
+            path = remove_dot_segments(&r.path);
+            query = r.query.clone();
+        } else {
+            scheme = self.scheme.clone();
+            if r.authority.is_some() {
+                authority = r.authority.clone();
+                path = remove_dot_segments(&r.path);
+                query = r.query.clone();
+            } else {
+                authority = self.authority.clone();
+                if r.path.is_empty() {
+                    path = self.path.clone();
+                    query = if r.query.is_some() { r.query.clone() } else { self.query.clone() };
+                } else {
+                    path = if r.path.starts_with('/') { remove_dot_segments(&r.path) } else { remove_dot_segments(&self.merge(&r.path)) };
+                    query = r.query.clone();
+                }
+            }
+        }
+        UrlReference { scheme, authority, path, query, fragment: r.fragment.clone() }
+    }
+
+    /// Merges a relative path with the path of this reference, as in section 5.2.3 of the RFC.
+    fn merge(&self, path: &str) -> String {
+        if self.authority.is_some() && self.path.is_empty() {
+            return "/".to_owned() + path;
+        }
+        match self.path.rfind('/') {
+            Some(i) => self.path[..=i].to_owned() + path,
+            None => path.to_owned(),
+        }
+    }
+
+    /// Computes the reference that resolves against this reference as the base
+    /// into `target`, keeping the query and fragment of `target`.
+    ///
+    /// Paths are compared segment by segment after normalizing percent-encoding,
+    /// as in [`.equivalent`]. If `target` has another scheme or authority,
+    /// or either path is not absolute, `target` is returned as is.
+    ///
+    /// [`.equivalent`]: UrlReference::equivalent
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::UrlReference;
+    ///
+    /// let page = UrlReference::parse("/docs/guide/intro.html");
+    /// let link = |target: &str| page.relative(&UrlReference::parse(target)).to_string();
+    /// assert_eq!("../img/a.png?x#y", link("/docs/img/a.png?x#y"));
+    /// assert_eq!("setup.html#install", link("/docs/guide/setup.html#install"));
+    /// assert_eq!("#top", link("/docs/guide/intro.html#top"));
+    /// assert_eq!("./", link("/docs/guide/"));
+    /// assert_eq!("./", link("/docs/%67uide/"));
+    /// ```
+    pub fn relative(&self, target: &UrlReference) -> UrlReference {
+        let same_scheme = self.scheme.as_deref().map(str::to_ascii_lowercase) == target.scheme.as_deref().map(str::to_ascii_lowercase);
+        let same_authority = self.authority.as_deref().map(normalize_authority) == target.authority.as_deref().map(normalize_authority);
+        let base_path = remove_dot_segments(self.path_or_root());
+        let target_path = remove_dot_segments(target.path_or_root());
+        if !same_scheme || !same_authority || !base_path.starts_with('/') || !target_path.starts_with('/') {
+            return target.clone();
+        }
+
+        let base_segments: Vec<&str> = base_path[1..].split('/').collect();
+        let target_segments: Vec<&str> = target_path[1..].split('/').collect();
+        let base_dir = &base_segments[..base_segments.len() - 1];
+        let target_dir = &target_segments[..target_segments.len() - 1];
+        let common = base_dir.iter().zip(target_dir).take_while(|(a, b)| normalize_percent_encoding(a) == normalize_percent_encoding(b)).count();
+
+        let same_path = common == base_dir.len() && common == target_dir.len()
+            && normalize_percent_encoding(base_segments.last().unwrap()) == normalize_percent_encoding(target_segments.last().unwrap());
+        // an empty path keeps the base query unless the reference has its own
+        let path = if same_path && (target.query.is_some() || self.query.is_none()) && (target.query.is_some() || target.fragment.is_some()) {
+            String::new()
+        } else {
+            let mut path = "../".repeat(base_dir.len() - common) + &target_segments[common..].join("/");
+            if path.is_empty() {
+                path = "./".to_owned();
+            } else if path.starts_with('/') || path.split('/').next().unwrap().contains(':') {
+                // keep an empty first segment from reading as an authority,
+                // and a first segment with a colon from reading as a scheme
+                path.insert_str(0, "./");
+            }
+            path
+        };
+        UrlReference { scheme: None, authority: None, path, query: target.query.clone(), fragment: target.fragment.clone() }
+    }
+
+    fn path_or_root(&self) -> &str {
+        if self.authority.is_some() && self.path.is_empty() { "/" } else { &self.path }
+    }
+
+    /// Indicates whether two references are equivalent after the normalizations of
+    /// section 6.2.2 of the RFC: schemes and hosts are compared case-insensitively,
+    /// percent-encoding is normalized and dot segments are removed from paths.
+    ///
+    /// Percent-encoding is normalized by decoding unreserved characters, using uppercase
+    /// hexadecimal digits and encoding non-ASCII characters as UTF-8, so
+    /// `%7e`, `~`, `%c3%a9` and `é` compare equal to `~`, `~`, `%C3%A9` and `%C3%A9`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use file_paths::UrlReference;
+    ///
+    /// assert!(UrlReference::parse("HTTP://Example.com/%7euser/a/../b?q#f")
+    ///     .equivalent(&UrlReference::parse("http://example.com/~user/b?q#f")));
+    /// assert!(!UrlReference::parse("/a%2Fb").equivalent(&UrlReference::parse("/a/b")));
+    /// ```
+    pub fn equivalent(&self, other: &UrlReference) -> bool {
+        self.normalized() == other.normalized()
+    }
+
+    fn normalized(&self) -> UrlReference {
+        UrlReference {
+            scheme: self.scheme.as_deref().map(str::to_ascii_lowercase),
+            authority: self.authority.as_deref().map(normalize_authority),
+            path: remove_dot_segments(&normalize_percent_encoding(self.path_or_root())),
+            query: self.query.as_deref().map(normalize_percent_encoding),
+            fragment: self.fragment.as_deref().map(normalize_percent_encoding),
+        }
+    }
+}
+
+impl std::fmt::Display for UrlReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}:", scheme)?;
+        }
+        if let Some(authority) = &self.authority {
+            write!(f, "//{}", authority)?;
+        }
+        write!(f, "{}", self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{}", query)?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes the `.` and `..` segments of a URL path, as in section 5.2.4 of RFC 3986.
+///
+/// Unlike path resolution, empty segments are kept and a trailing dot segment
+/// leaves a trailing slash.
+///
+/// # Example
+///
+/// ```
+/// use file_paths::remove_dot_segments;
+/// assert_eq!("/a/g", remove_dot_segments("/a/b/c/./../../g"));
+/// assert_eq!("mid/6", remove_dot_segments("mid/content=5/../6"));
+/// assert_eq!("/a//c/", remove_dot_segments("/a//b/../c/."));
+/// ```
+pub fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::new();
+    let remove_last_segment = |output: &mut String| output.truncate(output.rfind('/').unwrap_or(0));
+    while !input.is_empty() {
+        if let Some(rest) = input.strip_prefix("../").or_else(|| input.strip_prefix("./")) {
+            input = rest;
+        } else if input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") {
+            input = &input[3..];
+            remove_last_segment(&mut output);
+        } else if input == "/.." {
+            input = "/";
+            remove_last_segment(&mut output);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = usize::from(input.starts_with('/'));
+            let end = input[start..].find('/').map_or(input.len(), |i| i + start);
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output
+}
+
+/// Normalizes percent-encoding, decoding unreserved characters, using uppercase
+/// hexadecimal digits and encoding non-ASCII characters as UTF-8.
+fn normalize_percent_encoding(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut r = String::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b == b'%' && i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() {
+            let decoded = u8::from_str_radix(&s[i + 1..i + 3], 16).unwrap();
+            if decoded.is_ascii_alphanumeric() || b"-._~".contains(&decoded) {
+                r.push(decoded as char);
+            } else {
+                r.push_str(&format!("%{:02X}", decoded));
+            }
+            i += 3;
+        } else if b.is_ascii() {
+            r.push(b as char);
+            i += 1;
+        } else {
+            r.push_str(&format!("%{:02X}", b));
+            i += 1;
+        }
+    }
+    r
+}
+
+/// Lowercases the host of an authority, keeping its user information.
+fn normalize_authority(authority: &str) -> String {
+    match authority.rfind('@') {
+        Some(i) => normalize_percent_encoding(&authority[..=i]) + &authority[i + 1..].to_ascii_lowercase(),
+        None => authority.to_ascii_lowercase(),
+    }
+}
@@ -0,0 +1,71 @@
+// Command record_go_vectors records the Go rows of conformance_vectors.tsv
+// by running path.Clean, filepath.Clean and filepath.Rel on the inputs of
+// the rows of the other ecosystems.
+//
+// Go only provides the Windows behavior of path/filepath on Windows, so run it
+// on Linux or macOS to record the rows of the common variant and on Windows
+// to record the rows of the windows variant:
+//
+//	go run tools/record_go_vectors.go src/conformance_vectors.tsv
+//
+// Go rows of the recorded variant are replaced and appended at the end of the file.
+package main
+
+import (
+	"os"
+	"path"
+	"path/filepath"
+	"runtime"
+	"strings"
+)
+
+func main() {
+	name := os.Args[1]
+	data, err := os.ReadFile(name)
+	if err != nil {
+		panic(err)
+	}
+	variant := "common"
+	if runtime.GOOS == "windows" {
+		variant = "windows"
+	}
+	var kept, recorded []string
+	seen := map[string]bool{}
+	for _, line := range strings.SplitAfter(string(data), "\n") {
+		fields := strings.Split(strings.TrimRight(line, "\r\n"), "\t")
+		if strings.HasPrefix(line, "#") || len(fields) != 6 || fields[1] != variant {
+			if line != "" {
+				kept = append(kept, line)
+			}
+			continue
+		}
+		if fields[0] == "go" {
+			continue
+		}
+		kept = append(kept, line)
+		input := strings.Join(fields[1:5], "\t")
+		if seen[input] {
+			continue
+		}
+		seen[input] = true
+		var output string
+		switch fields[2] {
+		case "normalize":
+			if variant == "common" {
+				output = path.Clean(fields[3])
+			} else {
+				output = filepath.Clean(fields[3])
+			}
+		case "relative":
+			relative, err := filepath.Rel(fields[3], fields[4])
+			if err != nil {
+				relative = "!"
+			}
+			output = relative
+		}
+		recorded = append(recorded, "go\t"+input+"\t"+output+"\n")
+	}
+	if err := os.WriteFile(name, []byte(strings.Join(append(kept, recorded...), "")), 0o644); err != nil {
+		panic(err)
+	}
+}